@@ -11,6 +11,7 @@ fn main() {
             GridConfig {
                 cell_width: Millimeter(20.0),
                 cell_height: Millimeter(20.0),
+                .. Default::default()
             });
 
     grid.insert_street(StreetNameRect {
@@ -19,6 +20,7 @@ fn main() {
         width: Millimeter(50.0),
         y_from_top: Millimeter(30.0),
         height: Millimeter(8.0),
+        .. Default::default()
     });
 
     let deduplicated = DeduplicatedRoads::from_streets(&grid.street_names());
@@ -0,0 +1,150 @@
+//! Expands abbreviated street names on the map to the full names for the index
+//!
+//! Space on the map often forces labels like `"Canterbury Rd"` or
+//! `"Kaiser-Wilh.-Str."`, while the index has to list the full name. Since
+//! different labels for the same street can use different abbreviations, the
+//! names have to be expanded before they are deduplicated.
+
+/// One rule for expanding an abbreviation. Names are split into words at
+/// spaces and hyphens, i.e. `"Kaiser-Wilh.-Str."` is `["Kaiser", "Wilh.", "Str."]`.
+/// All rules are case-sensitive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbbreviationRule {
+    /// Replaces any word that is equal to the abbreviation: `"Wilh." -> "Wilhelm"`
+    Word { abbreviation: String, expansion: String },
+    /// Replaces the last word, if it is equal to the abbreviation. Useful for
+    /// abbreviations that are ambiguous in other positions, i.e. `"St" -> "Street"`,
+    /// which shouldn't expand `"St Mary's Road"`.
+    LastWord { abbreviation: String, expansion: String },
+    /// Replaces the end of a word, for compound words: `"Hauptstr." -> "Hauptstraße"`
+    Suffix { abbreviation: String, expansion: String },
+}
+
+/// Rule-based expander for abbreviated street names, i.e.:
+///
+/// ```rust
+/// # use street_index::abbreviations::AbbreviationExpander;
+/// let expander = AbbreviationExpander::new()
+///     .word("Wilh.", "Wilhelm")
+///     .word("Str.", "Straße")
+///     .suffix("str.", "straße");
+///
+/// assert_eq!(expander.expand("Kaiser-Wilh.-Str."), "Kaiser-Wilhelm-Straße");
+/// assert_eq!(expander.expand("Hauptstr."), "Hauptstraße");
+/// ```
+///
+/// For each word, the first matching rule is applied.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AbbreviationExpander {
+    pub rules: Vec<AbbreviationRule>,
+}
+
+impl AbbreviationExpander {
+
+    /// Creates an expander without any rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Common English street type abbreviations (`Rd`, `St`, `Ave`, ...),
+    /// expanded only at the end of the name
+    pub fn english() -> Self {
+        let mut expander = Self::new();
+        for (abbreviation, expansion) in &[
+            ("Rd", "Road"), ("St", "Street"), ("Ave", "Avenue"), ("Ln", "Lane"),
+            ("Dr", "Drive"), ("Sq", "Square"), ("Pl", "Place"), ("Ct", "Court"),
+            ("Cres", "Crescent"), ("Tce", "Terrace"), ("Blvd", "Boulevard"),
+        ] {
+            expander = expander
+                .last_word(abbreviation, expansion)
+                .last_word(&format!("{}.", abbreviation), expansion);
+        }
+        expander
+    }
+
+    /// Common German street name abbreviations (`Str.`, `Pl.`, `-str.`, ...)
+    pub fn german() -> Self {
+        Self::new()
+            .word("Str.", "Straße")
+            .word("Pl.", "Platz")
+            .word("Wilh.", "Wilhelm")
+            .word("Friedr.", "Friedrich")
+            .suffix("str.", "straße")
+            .suffix("pl.", "platz")
+    }
+
+    /// Adds a rule that replaces any word equal to `abbreviation`
+    pub fn word(mut self, abbreviation: &str, expansion: &str) -> Self {
+        self.rules.push(AbbreviationRule::Word { abbreviation: abbreviation.to_string(), expansion: expansion.to_string() });
+        self
+    }
+
+    /// Adds a rule that replaces the last word if it is equal to `abbreviation`
+    pub fn last_word(mut self, abbreviation: &str, expansion: &str) -> Self {
+        self.rules.push(AbbreviationRule::LastWord { abbreviation: abbreviation.to_string(), expansion: expansion.to_string() });
+        self
+    }
+
+    /// Adds a rule that replaces the end of any word ending with `abbreviation`
+    pub fn suffix(mut self, abbreviation: &str, expansion: &str) -> Self {
+        self.rules.push(AbbreviationRule::Suffix { abbreviation: abbreviation.to_string(), expansion: expansion.to_string() });
+        self
+    }
+
+    /// Expands all abbreviations in the name. Spaces and hyphens are preserved.
+    pub fn expand(&self, name: &str) -> String {
+
+        let is_separator = |c: char| c == ' ' || c == '-';
+        let separators = name.chars().filter(|c| is_separator(*c)).collect::<Vec<char>>();
+        let words = name.split(is_separator).collect::<Vec<&str>>();
+        let last_word = words.iter().rposition(|word| !word.is_empty());
+
+        let mut expanded = String::with_capacity(name.len());
+
+        for (index, word) in words.iter().enumerate() {
+            let is_last_word = Some(index) == last_word;
+            expanded.push_str(&self.expand_word(word, is_last_word));
+            if let Some(separator) = separators.get(index) {
+                expanded.push(*separator);
+            }
+        }
+
+        expanded
+    }
+
+    /// Applies the first matching rule to a single word
+    fn expand_word(&self, word: &str, is_last_word: bool) -> String {
+        use self::AbbreviationRule::*;
+
+        for rule in &self.rules {
+            match rule {
+                Word { abbreviation, expansion } if word == abbreviation => {
+                    return expansion.clone();
+                },
+                LastWord { abbreviation, expansion } if is_last_word && word == abbreviation => {
+                    return expansion.clone();
+                },
+                Suffix { abbreviation, expansion } if word.len() > abbreviation.len() && word.ends_with(abbreviation.as_str()) => {
+                    return format!("{}{}", &word[..word.len() - abbreviation.len()], expansion);
+                },
+                _ => { },
+            }
+        }
+
+        word.to_string()
+    }
+}
+
+#[test]
+fn test_expand_abbreviations() {
+    let english = AbbreviationExpander::english();
+    assert_eq!(english.expand("Canterbury Rd"), "Canterbury Road");
+    assert_eq!(english.expand("St Mary's St."), "St Mary's Street");
+    assert_eq!(english.expand("Canterbury Road"), "Canterbury Road");
+
+    let german = AbbreviationExpander::german();
+    assert_eq!(german.expand("Kaiser-Wilh.-Str."), "Kaiser-Wilhelm-Straße");
+    assert_eq!(german.expand("Friedr.-Ebert-Pl."), "Friedrich-Ebert-Platz");
+    assert_eq!(german.expand("Hauptstr."), "Hauptstraße");
+    assert_eq!(german.expand("Str."), "Straße");
+}
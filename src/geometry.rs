@@ -0,0 +1,181 @@
+//! Geometric primitives on the page and polygon helpers
+//!
+//! All coordinates are relative to the top left of the page / grid.
+
+use units::{Millimeter, Float};
+
+/// Position on the page, relative to the top left of the grid
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PagePoint {
+    pub x_from_left: Millimeter,
+    pub y_from_top: Millimeter,
+}
+
+impl PagePoint {
+    /// Creates a new point from any unit that can be converted to millimeter
+    pub fn new<U: Into<Millimeter>>(x_from_left: U, y_from_top: U) -> Self {
+        Self {
+            x_from_left: x_from_left.into(),
+            y_from_top: y_from_top.into(),
+        }
+    }
+}
+
+/// Axis-aligned rectangle on the page
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PageRect {
+    pub x_from_left: Millimeter,
+    pub y_from_top: Millimeter,
+    pub width: Millimeter,
+    pub height: Millimeter,
+}
+
+impl PageRect {
+    /// Creates a new rectangle from any unit that can be converted to millimeter
+    pub fn new<U: Into<Millimeter>>(x_from_left: U, y_from_top: U, width: U, height: U) -> Self {
+        Self {
+            x_from_left: x_from_left.into(),
+            y_from_top: y_from_top.into(),
+            width: width.into(),
+            height: height.into(),
+        }
+    }
+
+    /// Area of the rectangle in square millimeter
+    pub fn area(&self) -> Float {
+        self.width.0 * self.height.0
+    }
+
+    /// Area of the part that both rectangles have in common, in square millimeter
+    pub fn overlap_area(&self, other: &PageRect) -> Float {
+        let width = (self.x_from_left.0 + self.width.0).min(other.x_from_left.0 + other.width.0) - self.x_from_left.0.max(other.x_from_left.0);
+        let height = (self.y_from_top.0 + self.height.0).min(other.y_from_top.0 + other.height.0) - self.y_from_top.0.max(other.y_from_top.0);
+        width.max(0.0) * height.max(0.0)
+    }
+
+    /// Returns the smallest rectangle that contains all of the rectangles,
+    /// or `None` if there are no rectangles
+    pub fn bounding_box(rects: &[PageRect]) -> Option<PageRect> {
+        let first = rects.first()?;
+        let (mut left, mut top) = (first.x_from_left.0, first.y_from_top.0);
+        let (mut right, mut bottom) = (left + first.width.0, top + first.height.0);
+
+        for rect in &rects[1..] {
+            left = left.min(rect.x_from_left.0);
+            top = top.min(rect.y_from_top.0);
+            right = right.max(rect.x_from_left.0 + rect.width.0);
+            bottom = bottom.max(rect.y_from_top.0 + rect.height.0);
+        }
+
+        Some(PageRect::new(Millimeter(left), Millimeter(top), Millimeter(right - left), Millimeter(bottom - top)))
+    }
+
+    /// Distance between the closest edges of the two rectangles (0 if they overlap)
+    pub fn distance(&self, other: &PageRect) -> Float {
+        let gap_x = (other.x_from_left.0 - (self.x_from_left.0 + self.width.0)).max(self.x_from_left.0 - (other.x_from_left.0 + other.width.0)).max(0.0);
+        let gap_y = (other.y_from_top.0 - (self.y_from_top.0 + self.height.0)).max(self.y_from_top.0 - (other.y_from_top.0 + other.height.0)).max(0.0);
+        (gap_x * gap_x + gap_y * gap_y).sqrt()
+    }
+}
+
+/// Returns the area of a polygon in square millimeter (shoelace formula).
+/// The polygon doesn't have to be closed (first point == last point) and
+/// may be in clockwise or counter-clockwise order, but it must not intersect itself.
+pub fn polygon_area(polygon: &[PagePoint]) -> Float {
+    if polygon.len() < 3 {
+        return 0.0;
+    }
+
+    let mut double_area = 0.0;
+    for (i, a) in polygon.iter().enumerate() {
+        let b = &polygon[(i + 1) % polygon.len()];
+        double_area += a.x_from_left.0 * b.y_from_top.0 - b.x_from_left.0 * a.y_from_top.0;
+    }
+
+    (double_area / 2.0).abs()
+}
+
+/// Returns whether the point lies inside of the polygon (even-odd rule).
+/// Points exactly on the outline may be inside or outside.
+pub fn contains_point(polygon: &[PagePoint], point: PagePoint) -> bool {
+    let (x, y) = (point.x_from_left.0, point.y_from_top.0);
+    let mut inside = false;
+
+    for (i, a) in polygon.iter().enumerate() {
+        let b = &polygon[(i + 1) % polygon.len()];
+        let (ax, ay, bx, by) = (a.x_from_left.0, a.y_from_top.0, b.x_from_left.0, b.y_from_top.0);
+        // does the horizontal ray from the point to the right cross the edge?
+        if (ay > y) != (by > y) && x < ax + (y - ay) / (by - ay) * (bx - ax) {
+            inside = !inside;
+        }
+    }
+
+    inside
+}
+
+/// Clips a polygon to a rectangle (Sutherland-Hodgman), i.e. to calculate
+/// how much of a cell a polygon covers. Returns an empty polygon if
+/// the polygon lies completely outside of the rectangle.
+pub fn clip_polygon(polygon: &[PagePoint], rect: PageRect) -> Vec<PagePoint> {
+
+    let (left, top) = (rect.x_from_left.0, rect.y_from_top.0);
+    let (right, bottom) = (left + rect.width.0, top + rect.height.0);
+
+    // Each edge of the rectangle: is a point inside of the edge + where
+    // does the line between two points cross the edge?
+    let x = |p: &PagePoint| p.x_from_left.0;
+    let y = |p: &PagePoint| p.y_from_top.0;
+    let at_x = |a: &PagePoint, b: &PagePoint, edge: Float| {
+        let t = (edge - x(a)) / (x(b) - x(a));
+        PagePoint { x_from_left: Millimeter(edge), y_from_top: Millimeter(y(a) + t * (y(b) - y(a))) }
+    };
+    let at_y = |a: &PagePoint, b: &PagePoint, edge: Float| {
+        let t = (edge - y(a)) / (y(b) - y(a));
+        PagePoint { x_from_left: Millimeter(x(a) + t * (x(b) - x(a))), y_from_top: Millimeter(edge) }
+    };
+
+    let mut output = polygon.to_vec();
+
+    output = clip_edge(&output, |p| x(p) >= left, |a, b| at_x(a, b, left));
+    output = clip_edge(&output, |p| x(p) <= right, |a, b| at_x(a, b, right));
+    output = clip_edge(&output, |p| y(p) >= top, |a, b| at_y(a, b, top));
+    output = clip_edge(&output, |p| y(p) <= bottom, |a, b| at_y(a, b, bottom));
+
+    output
+}
+
+/// One step of the Sutherland-Hodgman algorithm: clips the polygon on one edge
+fn clip_edge<F, I>(polygon: &[PagePoint], is_inside: F, intersection: I) -> Vec<PagePoint>
+    where F: Fn(&PagePoint) -> bool, I: Fn(&PagePoint, &PagePoint) -> PagePoint
+{
+    let mut output = Vec::new();
+
+    for (i, current) in polygon.iter().enumerate() {
+        let previous = &polygon[(i + polygon.len() - 1) % polygon.len()];
+        match (is_inside(previous), is_inside(current)) {
+            (true, true) => output.push(*current),
+            (true, false) => output.push(intersection(previous, current)),
+            (false, true) => {
+                output.push(intersection(previous, current));
+                output.push(*current);
+            },
+            (false, false) => { },
+        }
+    }
+
+    output
+}
+
+#[test]
+fn test_clip_polygon() {
+    // triangle, half of it is inside of the rect
+    let triangle = [
+        PagePoint::new(Millimeter(0.0), Millimeter(0.0)),
+        PagePoint::new(Millimeter(20.0), Millimeter(0.0)),
+        PagePoint::new(Millimeter(0.0), Millimeter(20.0)),
+    ];
+
+    assert_eq!(polygon_area(&triangle), 200.0);
+    assert_eq!(polygon_area(&clip_polygon(&triangle, PageRect::new(Millimeter(0.0), Millimeter(0.0), Millimeter(10.0), Millimeter(20.0)))), 150.0);
+    assert_eq!(polygon_area(&clip_polygon(&triangle, PageRect::new(Millimeter(50.0), Millimeter(0.0), Millimeter(10.0), Millimeter(10.0)))), 0.0);
+}
@@ -1,4 +1,11 @@
-use roads2csv::{InputStreetValue, StreetName, GridPosition};
+use std::collections::{BTreeMap, BTreeSet};
+use roads2csv::{InputStreetValue, InputAddress, StreetName, IndexName, GridPosition, Category};
+
+pub use units::Millimeter;
+pub use geometry::{PagePoint, PageRect};
+use units::Float;
+use geometry::{clip_polygon, polygon_area, contains_point};
+use abbreviations::AbbreviationExpander;
 
 /// The Grid is your street-name grid. Right now there is
 /// no support for curved / rotated / translated grids.
@@ -10,18 +17,26 @@ use roads2csv::{InputStreetValue, StreetName, GridPosition};
 /// The `config` is for future use to be extended - right now
 /// it only stores how big the cells should be. In normal
 /// cartography, grids are usually 5 x 5 centimeters (i.e. 50 x 50 mm).
+///
+/// Internally, the grid is stored as a list of vertical and horizontal
+/// grid lines, so cells don't have to be equally sized (see `Grid::from_lines`).
 #[derive(Debug, Clone)]
 pub struct Grid {
     pub bbox: Bbox,
     pub config: GridConfig,
+    /// X positions of the vertical grid lines, sorted from left to right
+    column_lines: Vec<Millimeter>,
+    /// Y positions of the horizontal grid lines, sorted from top to bottom
+    row_lines: Vec<Millimeter>,
+    /// District boundaries for assigning localities to street names
+    districts: Vec<District>,
+    /// Rules for expanding abbreviated street names to the full name
+    abbreviations: AbbreviationExpander,
     fonts: Vec<InputStreetValue>,
+    /// House numbers of the address points, see `insert_address`
+    addresses: Vec<InputAddress>,
 }
 
-/// Unit struct just so it's easier to read that certain values
-/// should be in millimeter scale.
-#[derive(Debug, Copy, Clone)]
-pub struct Millimeter(pub f32);
-
 /// Bounding box (usually the page extents)
 #[derive(Debug, Copy, Clone)]
 pub struct Bbox {
@@ -29,9 +44,21 @@ pub struct Bbox {
     pub height: Millimeter,
 }
 
+impl Bbox {
+    /// Creates a bounding box from any unit that can be converted to
+    /// millimeter, i.e. `Bbox::new(Point(595.0), Point(842.0))` for an A4 page
+    pub fn new<U: Into<Millimeter>>(width: U, height: U) -> Self {
+        Self {
+            width: width.into(),
+            height: height.into(),
+        }
+    }
+}
+
 /// Later on this struct will be extended with parameters for
 /// curving, rotations, offsets, labeling, etc. Right now
-/// it's just: how big should one cell be?
+/// it's just: how big should one cell be and what should happen
+/// to the cells at the right / bottom edge of the page?
 ///
 /// Cells start at the top left (again, later on this will
 /// likely be configurable although I haven't seen a map where
@@ -40,6 +67,96 @@ pub struct Bbox {
 pub struct GridConfig {
     pub cell_height: Millimeter,
     pub cell_width: Millimeter,
+    /// What to do with the last column / row if the page size
+    /// isn't a multiple of the cell size
+    pub partial_cells: PartialCellPolicy,
+    /// How much a street name has to overlap a column / row
+    /// at its edges in order to be assigned to it
+    pub min_overlap: MinimumOverlap,
+    /// Which of the cells that a street name covers are used in the index
+    pub assignment: AssignmentStrategy,
+}
+
+impl Default for GridConfig {
+    /// 50 x 50 mm cells, partial cells are kept as their own column / row,
+    /// any overlap is enough to assign a cell, street names are assigned to
+    /// the cells at their corners
+    fn default() -> Self {
+        Self {
+            cell_height: Millimeter(50.0),
+            cell_width: Millimeter(50.0),
+            partial_cells: PartialCellPolicy::Keep,
+            min_overlap: MinimumOverlap::Any,
+            assignment: AssignmentStrategy::Corners,
+        }
+    }
+}
+
+impl GridConfig {
+    /// Creates a configuration with the default settings and the given cell size,
+    /// in any unit that can be converted to millimeter, i.e. `Inch(2.0)`
+    pub fn with_cell_size<U: Into<Millimeter>>(cell_width: U, cell_height: U) -> Self {
+        Self {
+            cell_width: cell_width.into(),
+            cell_height: cell_height.into(),
+            .. Default::default()
+        }
+    }
+}
+
+/// If the page width isn't a multiple of the cell width, the last column
+/// is only a sliver of a cell (same for the last row). Publishers handle
+/// this differently, so this controls how the grid lines are generated.
+///
+/// Only has an effect on grids created via `Grid::new`, not on grids
+/// with explicit grid lines.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PartialCellPolicy {
+    /// The partial cell is its own (narrower) column / row, i.e. for a
+    /// 110 mm wide page and 50 mm cells: `A = 0-50, B = 50-100, C = 100-110`
+    Keep,
+    /// The partial cell is merged into the previous column / row:
+    /// `A = 0-50, B = 50-110`
+    Merge,
+    /// The partial cell doesn't get a column / row, street names inside
+    /// of it are not indexed: `A = 0-50, B = 50-100`
+    Ignore,
+}
+
+/// Font metrics (descenders, side bearings) or rounding in the renderer often
+/// make a street name poke into the next column / row by a fraction of a
+/// millimeter. This removes such spurious cells at the edges of a street name.
+///
+/// The overlap is measured per axis: a column at the left / right edge of the
+/// street name is only assigned if the street name overlaps it by at least
+/// the given amount horizontally (same for rows, vertically). A street name is
+/// always assigned to at least one column and one row, even if it doesn't
+/// overlap any of them by the minimum amount.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MinimumOverlap {
+    /// Any overlap is enough. Street names that only touch a grid line
+    /// (i.e. zero overlap) are still not assigned to the cell behind the line.
+    Any,
+    /// The street name has to overlap the column / row by at least this length
+    Length(Millimeter),
+    /// The street name has to overlap the column / row with at least this
+    /// percentage (0 - 100) of its area
+    Percent(Float),
+}
+
+/// House styles differ in which cells a street name should be indexed by.
+/// For a street name that covers the cells `A1 - C2`:
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AssignmentStrategy {
+    /// The cells at the corners of the street name: `A1, A2, C1, C2`
+    Corners,
+    /// Every cell that the street name covers: `A1, A2, B1, B2, C1, C2`
+    FullCoverage,
+    /// Only the cell containing the center of the street name, i.e. `B1`.
+    /// If the center is outside of the grid, the street name isn't indexed.
+    Center,
+    /// Only the cell that contains the largest part of the street name
+    MajorityArea,
 }
 
 /// Represents one street name, layouted on the map. The `StreetNameRect`
@@ -55,91 +172,1280 @@ pub struct GridConfig {
 /// For cartographic projections, you have to project the
 /// fonts into this coordinate space before adding them, obviously.
 /// `street_index` does not take care of any geographic reprojections.
-#[derive(Debug, Clone)]
+#[derive(Debug, Default, Clone)]
 pub struct StreetNameRect {
     pub street_name: String,
     pub x_from_left: Millimeter,
     pub y_from_top: Millimeter,
     pub width: Millimeter,
     pub height: Millimeter,
+    /// Which index the street name belongs to (`Category::Street` by default)
+    pub category: Category,
+    /// Municipality / district of the street, to tell apart streets with the same
+    /// name in the index: `"Hauptstraße (Altdorf) B2"`
+    pub locality: Option<String>,
+    /// Full name of the street for the index, if the `street_name` on the map
+    /// is abbreviated (i.e. `"Canterbury Road"` for the label `"Canterbury Rd"`).
+    /// If this is not set, the abbreviation rules of the `Grid` are used.
+    pub index_name: Option<String>,
+    /// Extents of further fragments of the same label, if the label is broken
+    /// over several lines (`"Friedrich-Ebert-"` / `"Straße"`). The extents of the
+    /// `StreetNameRect` itself are the extents of the first fragment.
+    /// See `merge_label_fragments` for joining fragments automatically.
+    pub fragments: Vec<PageRect>,
+    /// Other official names of the street (bilingual names, former names),
+    /// listed in the index as configured by `IndexSettings::alternate_names`.
+    /// Alternate names are not abbreviated, so they are not expanded.
+    pub alternate_names: Vec<String>,
+}
+
+/// Centerline of a road, as a polyline on the page. Unlike a `StreetNameRect`,
+/// this indexes the road by every cell the road passes through, which some
+/// products (delivery maps, emergency maps) require.
+///
+/// Like the street names, the line has to be projected into the page
+/// coordinate space before adding it.
+#[derive(Debug, Clone)]
+pub struct RoadLine {
+    pub street_name: String,
+    pub points: Vec<PagePoint>,
+    pub category: Category,
+    /// Municipality / district of the road, see `StreetNameRect::locality`
+    pub locality: Option<String>,
+    /// Other official names of the road, see `StreetNameRect::alternate_names`
+    pub alternate_names: Vec<String>,
+}
+
+/// Named area on the map, such as a park, a lake, a square or a district.
+///
+/// Areas are either referenced by the cells that their polygon covers or
+/// like a street name, by the cells of their label (see `AreaReference`).
+#[derive(Debug, Clone)]
+pub struct AreaFeature {
+    pub name: String,
+    /// Outline of the area on the page. Doesn't have to be closed
+    /// (first point == last point), but must not intersect itself.
+    pub outline: Vec<PagePoint>,
+    pub reference: AreaReference,
+    /// Which index the area belongs to, usually `Category::Park` or `Category::Place`
+    pub category: Category,
+}
+
+/// How an `AreaFeature` is referenced in the index
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AreaReference {
+    /// By every cell that the outline covers with at least this percentage
+    /// (0 - 100) of the cell area. `0.0` means any overlap is enough.
+    Coverage(Float),
+    /// By the cells of the label of the area, like a street name
+    Label(PageRect),
+}
+
+/// Boundary of a named district / municipality. Street names without a
+/// locality are assigned to the district that contains most of them,
+/// see `Grid::add_district`.
+#[derive(Debug, Clone)]
+pub struct District {
+    pub name: String,
+    /// Boundary of the district on the page. Doesn't have to be closed
+    /// (first point == last point), but must not intersect itself.
+    pub outline: Vec<PagePoint>,
+}
+
+/// Point of interest (hospital, school, station, ...), which is represented by
+/// a symbol on the map instead of a text label. Listed in the index with its
+/// symbol code, i.e. `"Central Station ⟨rail⟩ C4"`.
+#[derive(Debug, Clone)]
+pub struct PointFeature {
+    pub name: String,
+    /// Center of the symbol
+    pub position: PagePoint,
+    /// Radius of the symbol. If set, the point of interest is assigned to the
+    /// cells of the bounding box of the symbol like a street name, otherwise
+    /// only to the cell that contains the `position`.
+    pub symbol_radius: Option<Millimeter>,
+    /// Symbol code, i.e. `"rail"` or `"hospital"`
+    pub symbol: Option<String>,
+    /// Which index the point belongs to, usually `Category::PointOfInterest`
+    pub category: Category,
+}
+
+/// Address point (i.e. the position of a house number on the map or from an
+/// address database), used for listing house number ranges in the index:
+/// `"Canterbury Road 1-47 B2, 48-120 B3"`, see `Grid::insert_address`.
+#[derive(Debug, Clone)]
+pub struct AddressPoint {
+    /// Name of the street that the address belongs to. Abbreviations are
+    /// expanded like the names of the street labels.
+    pub street_name: String,
+    /// House number, i.e. `"12"` or `"12a"`
+    pub house_number: String,
+    pub position: PagePoint,
+    /// Municipality / district of the street, see `StreetNameRect::locality`
+    pub locality: Option<String>,
 }
 
+impl StreetNameRect {
+    /// Creates a new street name from any unit that can be converted to
+    /// millimeter, so that the extents from the renderer can be used directly,
+    /// i.e. `StreetNameRect::new("Canterbury Road", Point(85.0), Point(85.0), Point(141.7), Point(22.7))`
+    pub fn new<S: Into<String>, U: Into<Millimeter>>(street_name: S, x_from_left: U, y_from_top: U, width: U, height: U) -> Self {
+        Self {
+            street_name: street_name.into(),
+            x_from_left: x_from_left.into(),
+            y_from_top: y_from_top.into(),
+            width: width.into(),
+            height: height.into(),
+            category: Category::Street,
+            locality: None,
+            index_name: None,
+            fragments: Vec::new(),
+            alternate_names: Vec::new(),
+        }
+    }
+
+    /// Returns the extents of the street name, without the name
+    /// (only of the first fragment, see `all_fragments`)
+    pub fn rect(&self) -> PageRect {
+        PageRect {
+            x_from_left: self.x_from_left,
+            y_from_top: self.y_from_top,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// Returns the extents of all fragments of the street name, starting with the first one
+    pub fn all_fragments(&self) -> Vec<PageRect> {
+        let mut fragments = vec![self.rect()];
+        fragments.extend(self.fragments.iter().cloned());
+        fragments
+    }
+}
 
 impl Grid {
 
     /// Initializes an empty grid from a bounding box + configuration
+    ///
+    /// The grid lines are placed every `cell_width` / `cell_height` millimeter,
+    /// starting at the top left. If the page size isn't a multiple of the cell
+    /// size, the last column / row is handled according to `config.partial_cells`.
     pub fn new(bbox: Bbox, config: GridConfig) -> Self {
         Self {
             bbox,
             config,
+            column_lines: uniform_grid_lines(bbox.width, config.cell_width, config.partial_cells),
+            row_lines: uniform_grid_lines(bbox.height, config.cell_height, config.partial_cells),
+            districts: Vec::new(),
+            abbreviations: AbbreviationExpander::new(),
             fonts: Vec::new(),
+            addresses: Vec::new(),
         }
     }
 
+    /// Initializes an empty grid from explicit grid line positions, i.e.
+    /// `[0, 50, 100, 130]` for three columns where the last one is only 30 mm wide.
+    /// Use this if the grid lines have to avoid the legend or the cells can't be
+    /// equally sized for other reasons.
+    ///
+    /// The lines don't have to start at 0, but there have to be at least two lines
+    /// per axis (the left / right or top / bottom border). The `cell_width` and
+    /// `cell_height` of the `config` are ignored, since the lines are given explicitly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are less than two distinct column lines or row lines
+    /// (lines that are `NaN` are ignored).
+    pub fn from_lines(column_lines: Vec<Millimeter>, row_lines: Vec<Millimeter>, config: GridConfig) -> Self {
+        let column_lines = sorted_grid_lines(column_lines);
+        let row_lines = sorted_grid_lines(row_lines);
+
+        assert!(column_lines.len() >= 2, "grid needs at least two column lines");
+        assert!(row_lines.len() >= 2, "grid needs at least two row lines");
+
+        let bbox = Bbox {
+            width: Millimeter(column_lines[column_lines.len() - 1].0 - column_lines[0].0),
+            height: Millimeter(row_lines[row_lines.len() - 1].0 - row_lines[0].0),
+        };
+
+        Self {
+            bbox,
+            config,
+            column_lines,
+            row_lines,
+            districts: Vec::new(),
+            abbreviations: AbbreviationExpander::new(),
+            fonts: Vec::new(),
+            addresses: Vec::new(),
+        }
+    }
+
+    /// Returns how many columns the grid has, i.e. `3` for a grid with the columns `A - C`
+    pub fn column_count(&self) -> usize {
+        self.column_lines.len() - 1
+    }
+
+    /// Returns how many rows the grid has, i.e. `3` for a grid with the rows `1 - 3`
+    pub fn row_count(&self) -> usize {
+        self.row_lines.len() - 1
+    }
+
+    /// Returns the X positions of the vertical grid lines, from left to right
+    pub fn column_lines(&self) -> &[Millimeter] {
+        &self.column_lines
+    }
+
+    /// Returns the Y positions of the horizontal grid lines, from top to bottom
+    pub fn row_lines(&self) -> &[Millimeter] {
+        &self.row_lines
+    }
+
     /// Inserts a street and assigns a `GridPosition` (such as "A2" or "B4") to the
     /// road. Note that a `StreetNameRect` may span more than one rectangle, in which
-    /// case the road name will be duplicated
+    /// case the road name will be duplicated. Which of the rectangles are used
+    /// depends on the `config.assignment` strategy.
+    ///
+    /// Parts of the street name that lie outside of the grid are clipped off. If the
+    /// street name lies completely outside of the grid, it isn't inserted at all.
+    ///
+    /// If the street name has no `locality`, but districts were added to the grid
+    /// (see `add_district`), the district that contains most of the street name is
+    /// used as the locality.
+    ///
+    /// The street is indexed by its `index_name` or, if that isn't set, by the
+    /// `street_name` with all abbreviations expanded (see `set_abbreviations`).
+    ///
+    /// For labels with several `fragments`, the cells of all fragments are used
+    /// (for the `Center` and `MajorityArea` strategies, the fragments are treated
+    /// as one label, so the street name is still only assigned to one cell).
     pub fn insert_street(&mut self, rect: StreetNameRect) {
+        let fragments = rect.all_fragments();
+        let positions_to_add = self.label_cells(&fragments);
+        let locality = rect.locality.clone().or_else(|| {
+            PageRect::bounding_box(&fragments).and_then(|bbox| self.district_at(bbox))
+        });
+        let index_name = rect.index_name.clone().unwrap_or_else(|| self.abbreviations.expand(&rect.street_name));
+        self.push_cells(&plain_name(&index_name, &locality), &rect.category, &rect.alternate_names, positions_to_add);
+    }
+
+    /// Sets the rules for expanding abbreviated street names (`"Canterbury Rd"`) to the
+    /// full names for the index. Applies to street names and road lines that are
+    /// inserted afterwards. By default, no abbreviations are expanded.
+    pub fn set_abbreviations(&mut self, abbreviations: AbbreviationExpander) {
+        self.abbreviations = abbreviations;
+    }
+
+    /// Registers a district, so that street names that are inserted afterwards
+    /// are automatically assigned to it as their locality. Districts have to be
+    /// added before the street names, since the locality is assigned on insertion.
+    pub fn add_district(&mut self, district: District) {
+        self.districts.push(district);
+    }
+
+    /// Returns the name of the district that contains the largest part of the rect
+    /// (the first one if several districts contain equally large parts).
+    fn district_at(&self, rect: PageRect) -> Option<String> {
+        let mut best: Option<(&District, Float)> = None;
+
+        for district in &self.districts {
+            let area = polygon_area(&clip_polygon(&district.outline, rect));
+            let is_better = match best {
+                Some((_, best_area)) => area > best_area,
+                None => area > 0.0,
+            };
+            if is_better {
+                best = Some((district, area));
+            }
+        }
+
+        best.map(|(district, _)| district.name.clone())
+    }
+
+    /// Inserts an area (park, lake, district, ...), either by the cells its outline
+    /// covers or by the cells of its label, depending on `area.reference`.
+    pub fn insert_area(&mut self, area: AreaFeature) {
+        let positions_to_add = match area.reference {
+            AreaReference::Coverage(min_coverage) => self.polygon_cells(&area.outline, min_coverage),
+            AreaReference::Label(label) => self.rect_cells(label),
+        };
+        self.push_cells(&plain_name(&area.name, &None), &area.category, &[], positions_to_add);
+    }
+
+    /// Inserts a point of interest, see `PointFeature`
+    pub fn insert_point(&mut self, point: PointFeature) {
+        let radius = point.symbol_radius.map(|r| r.0).unwrap_or(0.0);
+        let symbol_rect = PageRect {
+            x_from_left: Millimeter(point.position.x_from_left.0 - radius),
+            y_from_top: Millimeter(point.position.y_from_top.0 - radius),
+            width: Millimeter(2.0 * radius),
+            height: Millimeter(2.0 * radius),
+        };
+
+        let positions_to_add = self.rect_cells(symbol_rect);
+        let name = IndexName {
+            street_name: StreetName(point.name),
+            locality: None,
+            symbol: point.symbol,
+        };
+
+        self.push_cells(&name, &point.category, &[], positions_to_add);
+    }
+
+    /// Inserts the address point of a house number. The house number is assigned
+    /// to the cell that contains the `position` and (if the address has no locality)
+    /// to the district that contains the `position`.
+    ///
+    /// Only the leading digits of the house number are used (`"12a"` is `12`),
+    /// addresses without a number or outside of the grid are ignored.
+    /// Use `DeduplicatedRoads::add_house_numbers` to add them to the index.
+    pub fn insert_address(&mut self, address: AddressPoint) {
+        let digits = address.house_number.trim().chars().take_while(|c| c.is_ascii_digit()).collect::<String>();
+        let house_number = match digits.parse::<u32>() {
+            Ok(number) => number,
+            Err(_) => return,
+        };
+
+        let (x, y) = (address.position.x_from_left.0, address.position.y_from_top.0);
+        let (column, row) = match (cell_range(&self.column_lines, x, x), cell_range(&self.row_lines, y, y)) {
+            (Some((column, _)), Some((row, _))) => (column, row),
+            _ => return,
+        };
+
+        let position = address.position;
+        let locality = address.locality.or_else(|| {
+            self.districts.iter()
+            .find(|district| contains_point(&district.outline, position))
+            .map(|district| district.name.clone())
+        });
+
+        self.addresses.push(InputAddress {
+            street_name: StreetName(self.abbreviations.expand(&address.street_name)),
+            locality,
+            house_number,
+            position: GridPosition {
+                column: number_to_alphabet_value(column),
+                row: row + 1,
+            },
+        });
+    }
+
+    /// Returns all the address points in the grid that were added previously
+    pub fn addresses(&self) -> Vec<InputAddress> {
+        self.addresses.clone()
+    }
+
+    /// Returns the (column, row) cells that a street name consisting of
+    /// one or more fragments is assigned to, see `insert_street`
+    fn label_cells(&self, fragments: &[PageRect]) -> Vec<(usize, usize)> {
+
+        if fragments.len() == 1 {
+            return self.rect_cells(fragments[0]);
+        }
+
+        match self.config.assignment {
+            AssignmentStrategy::Center => {
+                match PageRect::bounding_box(fragments) {
+                    Some(bbox) => self.rect_cells(bbox),
+                    None => Vec::new(),
+                }
+            },
+            AssignmentStrategy::MajorityArea => {
+                // Sum up the overlap of all fragments per cell
+                let mut areas = BTreeMap::new();
+                for fragment in fragments {
+                    let (x_from, x_to) = (fragment.x_from_left.0, fragment.x_from_left.0 + fragment.width.0);
+                    let (y_from, y_to) = (fragment.y_from_top.0, fragment.y_from_top.0 + fragment.height.0);
+                    if let (Some(columns), Some(rows)) = (cell_range(&self.column_lines, x_from, x_to), cell_range(&self.row_lines, y_from, y_to)) {
+                        for column in columns.0..=columns.1 {
+                            for row in rows.0..=rows.1 {
+                                *areas.entry((column, row)).or_insert(0.0) += self.cell_rect(column, row).overlap_area(fragment);
+                            }
+                        }
+                    }
+                }
+
+                let mut largest: Option<((usize, usize), Float)> = None;
+                for (cell, area) in areas {
+                    if largest.map_or(true, |(_, largest_area)| area > largest_area) {
+                        largest = Some((cell, area));
+                    }
+                }
+                largest.map(|(cell, _)| cell).into_iter().collect()
+            },
+            AssignmentStrategy::Corners | AssignmentStrategy::FullCoverage => {
+                let mut cells = Vec::new();
+                for fragment in fragments {
+                    for cell in self.rect_cells(*fragment) {
+                        if !cells.contains(&cell) {
+                            cells.push(cell);
+                        }
+                    }
+                }
+                cells
+            },
+        }
+    }
+
+    /// Returns the (column, row) cells that a street name with the extents
+    /// of `rect` is assigned to, see `insert_street`
+    fn rect_cells(&self, rect: PageRect) -> Vec<(usize, usize)> {
 
         // ignore direction, etc. for now
-        let min_position_x = (rect.x_from_left.0 / self.config.cell_width.0).floor() as usize;
-        let max_position_x = ((rect.x_from_left.0 + rect.width.0) / self.config.cell_width.0).floor() as usize;
-
-        let mut min_position_y = (rect.y_from_top.0 / self.config.cell_height.0).floor() as usize;
-        let mut max_position_y = ((rect.y_from_top.0 + rect.height.0) / self.config.cell_height.0).floor() as usize;
-
-        // Y positions have to be adjusted by 1
-        // We don't want maps to start at row 0, but rather at row 1
-        min_position_y += 1;
-        max_position_y += 1;
-
-        let positions_to_add = match (min_position_x == max_position_x, min_position_y == max_position_y) {
-            (true, true) => {
-                // Street name is contained within one rectangle
-                vec![
-                    (number_to_alphabet_value(min_position_x), min_position_y),
-                ]
+        let (x_from, x_to) = (rect.x_from_left.0, rect.x_from_left.0 + rect.width.0);
+        let (min_position_x, max_position_x) = match cell_range(&self.column_lines, x_from, x_to) {
+            Some(s) => trim_cell_range(&self.column_lines, x_from, x_to, s, self.config.min_overlap),
+            None => return Vec::new(),
+        };
+
+        let (y_from, y_to) = (rect.y_from_top.0, rect.y_from_top.0 + rect.height.0);
+        let (min_position_y, max_position_y) = match cell_range(&self.row_lines, y_from, y_to) {
+            Some(s) => trim_cell_range(&self.row_lines, y_from, y_to, s, self.config.min_overlap),
+            None => return Vec::new(),
+        };
+
+        match self.config.assignment {
+            AssignmentStrategy::Corners => match (min_position_x == max_position_x, min_position_y == max_position_y) {
+                (true, true) => {
+                    // Street name is contained within one rectangle
+                    vec![
+                        (min_position_x, min_position_y),
+                    ]
+                },
+                (true, false) => {
+                    // Street name is contained within one column
+                    vec![
+                        (min_position_x, min_position_y),
+                        (min_position_x, max_position_y),
+                    ]
+                },
+                (false, true) => {
+                    // Street name is contained within one row
+                    vec![
+                        (min_position_x, min_position_y),
+                        (max_position_x, min_position_y),
+                    ]
+                },
+                (false, false) => {
+                    // Street name overlaps 4 quadrants
+                    vec![
+                        (min_position_x, min_position_y),
+                        (min_position_x, max_position_y),
+                        (max_position_x, min_position_y),
+                        (max_position_x, max_position_y),
+                    ]
+                }
             },
-            (true, false) => {
-                // Street name is contained within one column
-                vec![
-                    (number_to_alphabet_value(min_position_x), min_position_y),
-                    (number_to_alphabet_value(min_position_x), max_position_y),
-                ]
+            AssignmentStrategy::FullCoverage => {
+                (min_position_x..=max_position_x)
+                .flat_map(|x| (min_position_y..=max_position_y).map(move |y| (x, y)))
+                .collect()
             },
-            (false, true) => {
-                // Street name is contained within one row
-                vec![
-                    (number_to_alphabet_value(min_position_x), min_position_y),
-                    (number_to_alphabet_value(max_position_x), min_position_y),
-                ]
+            AssignmentStrategy::Center => {
+                let center_x = (x_from + x_to) / 2.0;
+                let center_y = (y_from + y_to) / 2.0;
+                match (cell_range(&self.column_lines, center_x, center_x), cell_range(&self.row_lines, center_y, center_y)) {
+                    (Some((x, _)), Some((y, _))) => vec![(x, y)],
+                    // Center of the street name is outside of the grid
+                    _ => Vec::new(),
+                }
             },
-            (false, false) => {
-                // Street name overlaps 4 quadrants
-                vec![
-                    (number_to_alphabet_value(min_position_x), min_position_y),
-                    (number_to_alphabet_value(min_position_x), max_position_y),
-                    (number_to_alphabet_value(max_position_x), min_position_y),
-                    (number_to_alphabet_value(max_position_x), max_position_y),
-                ]
-            }
+            AssignmentStrategy::MajorityArea => {
+                // The overlap area of a cell is (overlap width * overlap height),
+                // so the largest cell is in the column and row with the largest overlap
+                vec![(
+                    largest_overlap(&self.column_lines, x_from, x_to, (min_position_x, max_position_x)),
+                    largest_overlap(&self.row_lines, y_from, y_to, (min_position_y, max_position_y)),
+                )]
+            },
+        }
+    }
+
+    /// Returns the (column, row) cells that the polygon covers
+    /// with at least `min_coverage` percent of the cell area
+    fn polygon_cells(&self, polygon: &[PagePoint], min_coverage: Float) -> Vec<(usize, usize)> {
+
+        if polygon.is_empty() {
+            return Vec::new();
+        }
+
+        // Only look at the cells within the bounding box of the polygon
+        let min_max = |values: &mut dyn Iterator<Item=Float>| {
+            values.fold((Float::INFINITY, Float::NEG_INFINITY), |(min, max), v| (min.min(v), max.max(v)))
         };
+        let (x_from, x_to) = min_max(&mut polygon.iter().map(|p| p.x_from_left.0));
+        let (y_from, y_to) = min_max(&mut polygon.iter().map(|p| p.y_from_top.0));
 
-        for (column, row) in positions_to_add {
-            self.fonts.push(InputStreetValue {
-                street_name: StreetName(rect.street_name.clone()),
-                position: GridPosition {
-                    column,
-                    row,
+        let (columns, rows) = match (cell_range(&self.column_lines, x_from, x_to), cell_range(&self.row_lines, y_from, y_to)) {
+            (Some(c), Some(r)) => (c, r),
+            _ => return Vec::new(),
+        };
+
+        let mut cells = Vec::new();
+
+        for column in columns.0..=columns.1 {
+            for row in rows.0..=rows.1 {
+                let cell = self.cell_rect(column, row);
+                let covered_area = polygon_area(&clip_polygon(polygon, cell));
+                if covered_area > 0.0 && covered_area / cell.area() * 100.0 >= min_coverage {
+                    cells.push((column, row));
                 }
-            });
+            }
         }
+
+        cells
+    }
+
+    /// Returns the extents of the cell at a grid position, i.e. for drawing the
+    /// cells of a street. Returns `None` if the position is not inside of the grid.
+    pub fn cell_bounds(&self, position: &GridPosition) -> Option<PageRect> {
+        let column = alphabet_value_to_number(&position.column)?;
+        let row = position.row.checked_sub(1)?;
+        if column < self.column_count() && row < self.row_count() {
+            Some(self.cell_rect(column, row))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the extents of the cell at (column, row), both starting at 0
+    fn cell_rect(&self, column: usize, row: usize) -> PageRect {
+        let (left, right) = (self.column_lines[column], self.column_lines[column + 1]);
+        let (top, bottom) = (self.row_lines[row], self.row_lines[row + 1]);
+        PageRect {
+            x_from_left: left,
+            y_from_top: top,
+            width: Millimeter(right.0 - left.0),
+            height: Millimeter(bottom.0 - top.0),
+        }
+    }
+
+    /// Inserts the centerline of a road and assigns a `GridPosition` for every
+    /// cell that the line passes through. Cells that the line only touches at a
+    /// corner are not assigned. A segment that runs exactly along a grid line is
+    /// assigned to the cells on the higher side only, like a point on a grid line
+    /// (the cells to the right of a column line or below a row line, or the last
+    /// cells for the last line of the grid).
+    ///
+    /// The positions end up in the same list as the ones from `insert_street`, so
+    /// the road lines can be combined with (or used instead of) the street names:
+    /// `DeduplicatedRoads` merges the positions of both.
+    pub fn insert_road_line(&mut self, line: RoadLine) {
+
+        let mut cells = BTreeSet::new();
+
+        // A road line with a single point is indexed by the cell it's in
+        if line.points.len() == 1 {
+            cells.extend(segment_cells(&self.column_lines, &self.row_lines, line.points[0], line.points[0]));
+        }
+
+        for segment in line.points.windows(2) {
+            cells.extend(segment_cells(&self.column_lines, &self.row_lines, segment[0], segment[1]));
+        }
+
+        let index_name = self.abbreviations.expand(&line.street_name);
+        self.push_cells(&plain_name(&index_name, &line.locality), &line.category, &line.alternate_names, cells);
     }
 
     /// Returns all the fonts in the grid that were added previously
     pub fn street_names(&self) -> Vec<InputStreetValue> {
         self.fonts.clone()
     }
+
+    /// Adds the street name for every (column, row) cell, both starting at 0
+    fn push_cells<I: IntoIterator<Item=(usize, usize)>>(&mut self, name: &IndexName, category: &Category, alternate_names: &[String], cells: I) {
+        for (column, row) in cells {
+            self.fonts.push(InputStreetValue {
+                street_name: name.street_name.clone(),
+                symbol: name.symbol.clone(),
+                locality: name.locality.clone(),
+                alternate_names: alternate_names.iter().map(|name| StreetName(name.clone())).collect(),
+                position: GridPosition {
+                    column: number_to_alphabet_value(column),
+                    // Y positions have to be adjusted by 1
+                    // We don't want maps to start at row 0, but rather at row 1
+                    row: row + 1,
+                },
+                category: category.clone(),
+            });
+        }
+    }
+}
+
+/// Joins street name labels that the renderer emitted as separate text fragments,
+/// because the name was hyphenated over two lines (`"Friedrich-Ebert-"` / `"Straße"`).
+///
+/// A label that ends with a hyphen is joined with the closest label that is at most
+/// `max_distance` away and doesn't start above it (the next line or the rest of the
+/// line). If the second fragment starts with an uppercase letter, the hyphen is kept
+/// (`"Friedrich-Ebert-Straße"`), otherwise it was a syllable break and is removed
+/// (`"Canter-"` / `"bury Road"` = `"Canterbury Road"`). Chains of several fragments
+/// are joined as well. Labels that aren't hyphenated are never joined - use the
+/// `fragments` of a `StreetNameRect` for those.
+///
+/// The joined label has the extents, category, locality and index name of the
+/// first fragment, the other fragments are added to its `fragments`.
+pub fn merge_label_fragments(labels: Vec<StreetNameRect>, max_distance: Millimeter) -> Vec<StreetNameRect> {
+
+    let mut labels = labels.into_iter().map(Some).collect::<Vec<Option<StreetNameRect>>>();
+
+    for index in 0..labels.len() {
+        loop {
+            let current = match &labels[index] {
+                Some(label) if label.street_name.ends_with('-') => label,
+                _ => break,
+            };
+
+            let current_rect = *current.all_fragments().last().unwrap();
+
+            // Find the closest fragment that continues the label
+            let mut closest: Option<(usize, Float)> = None;
+            for (other_index, other) in labels.iter().enumerate() {
+                let other = match other {
+                    Some(other) if other_index != index => other,
+                    _ => continue,
+                };
+                let distance = current_rect.distance(&other.rect());
+                if other.y_from_top.0 >= current_rect.y_from_top.0 && distance <= max_distance.0 && closest.map_or(true, |(_, d)| distance < d) {
+                    closest = Some((other_index, distance));
+                }
+            }
+
+            let next = match closest {
+                Some((next_index, _)) => labels[next_index].take().unwrap(),
+                None => break,
+            };
+
+            let current = labels[index].as_mut().unwrap();
+            let keeps_hyphen = next.street_name.chars().next().is_some_and(char::is_uppercase);
+            if !keeps_hyphen {
+                current.street_name.pop();
+            }
+            current.street_name.push_str(&next.street_name);
+            current.fragments.extend(next.all_fragments());
+        }
+    }
+
+    labels.into_iter().flatten().collect()
+}
+
+/// Index name for a street without a symbol
+fn plain_name(street_name: &str, locality: &Option<String>) -> IndexName {
+    IndexName {
+        street_name: StreetName(street_name.to_string()),
+        locality: locality.clone(),
+        symbol: None,
+    }
+}
+
+/// Maximum number of cells of a uniform grid in each direction, see `uniform_grid_lines`
+const MAX_UNIFORM_CELLS: usize = 10_000;
+
+/// Places grid lines every `cell_size` millimeter from 0 to `extent`.
+/// If the last cell would be narrower than `cell_size`, the `partial_cells`
+/// policy decides where the last line is placed.
+///
+/// A zero-sized, negative or non-finite `extent` results in a single empty cell
+/// `[0, 0]`. A zero-sized, negative or non-finite `cell_size` results in one cell
+/// over the whole `extent`. Cells that are so small that there would be more than
+/// `MAX_UNIFORM_CELLS` of them are enlarged to `extent / MAX_UNIFORM_CELLS`.
+fn uniform_grid_lines(extent: Millimeter, cell_size: Millimeter, partial_cells: PartialCellPolicy) -> Vec<Millimeter> {
+    if extent.0 <= 0.0 || !extent.0.is_finite() {
+        return vec![Millimeter(0.0), Millimeter(0.0)];
+    }
+
+    // Zero-sized, negative or non-finite cells would never reach the end
+    // of the extent: treat the whole extent as one cell
+    let cell_size = if cell_size.0 > 0.0 && cell_size.0.is_finite() {
+        Millimeter(cell_size.0.max(extent.0 / MAX_UNIFORM_CELLS as Float))
+    } else {
+        extent
+    };
+
+    let mut lines = Vec::new();
+    let mut line_index = 0;
+    loop {
+        // Multiply instead of adding up the cell size, so that
+        // rounding errors don't accumulate over many cells
+        let position = line_index as Float * cell_size.0;
+        if position >= extent.0 || is_rounding_error(position, extent.0) {
+            break;
+        }
+        lines.push(Millimeter(position));
+        line_index += 1;
+    }
+
+    // Every line so far is the start of a cell - if the last cell
+    // doesn't fit on the page anymore, it's a partial cell
+    let last_cell_end = lines[lines.len() - 1].0 + cell_size.0;
+    let has_partial_cell = last_cell_end > extent.0 && !is_rounding_error(last_cell_end, extent.0);
+
+    match partial_cells {
+        PartialCellPolicy::Keep => { },
+        // Only merge if there is a previous cell to merge into
+        PartialCellPolicy::Merge if has_partial_cell && lines.len() > 1 => { lines.pop(); },
+        PartialCellPolicy::Merge => { },
+        // Only ignore the partial cell if there is at least one full cell left
+        PartialCellPolicy::Ignore if has_partial_cell && lines.len() > 1 => { return lines; },
+        PartialCellPolicy::Ignore => { },
+    }
+
+    lines.push(extent);
+    lines
+}
+
+/// Returns whether `a` and `b` only differ because of floating point rounding
+/// (i.e. `3.0 * 0.2` vs. `0.6`), relative to the size of the values.
+///
+/// Used when generating grid lines, so that a page which is a multiple of the
+/// cell size doesn't get a sliver of a cell at the edge.
+fn is_rounding_error(a: Float, b: Float) -> bool {
+    const MAX_ROUNDING_ERROR: Float = 4.0 * Float::EPSILON;
+    (a - b).abs() <= a.abs().max(b.abs()) * MAX_ROUNDING_ERROR
+}
+
+/// Sorts the grid lines and removes lines that were given twice
+fn sorted_grid_lines(mut lines: Vec<Millimeter>) -> Vec<Millimeter> {
+    lines.retain(|line| !line.0.is_nan());
+    lines.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    lines.dedup();
+    lines
+}
+
+/// Returns the (first, last) index of the cells that the range `[from, to]`
+/// covers, using a binary search over the grid lines.
+///
+/// Cell boundary rules (the same for `f32` and `f64`):
+///
+/// - Each cell includes its leading grid line, but not its trailing one, so a
+///   value exactly on a grid line belongs to the next cell (like `floor()`).
+///   Cells that a range only touches are removed later on by `trim_cell_range`.
+/// - The last grid line (right / bottom page border) still belongs to the last cell.
+/// - Values are compared exactly, without any tolerance - but grid lines are
+///   generated by multiplying the cell size (not by adding it up), so the line
+///   positions don't drift on grids with many cells.
+///
+/// Returns `None` if the range is completely outside of the grid or if
+/// there are less than two grid lines (no cells).
+fn cell_range(lines: &[Millimeter], from: Float, to: Float) -> Option<(usize, usize)> {
+    if lines.len() < 2 {
+        return None;
+    }
+
+    let (first_line, last_line) = (lines[0].0, lines[lines.len() - 1].0);
+    if to < first_line || from > last_line {
+        return None;
+    }
+
+    let cell_count = lines.len() - 1;
+    let cell_index = |position: Float| {
+        let lines_before = lines.partition_point(|line| line.0 <= position);
+        lines_before.saturating_sub(1).min(cell_count - 1)
+    };
+
+    Some((cell_index(from), cell_index(to)))
+}
+
+/// Returns by how much the range `[from, to]` overlaps the cell (negative if it doesn't overlap)
+fn cell_overlap(lines: &[Millimeter], cell: usize, from: Float, to: Float) -> Float {
+    to.min(lines[cell + 1].0) - from.max(lines[cell].0)
+}
+
+/// Returns the cell within the `range` that `[from, to]` overlaps the most
+/// (the first one if there are several equally large overlaps)
+fn largest_overlap(lines: &[Millimeter], from: Float, to: Float, range: (usize, usize)) -> usize {
+    let (mut largest, mut largest_overlap) = (range.0, cell_overlap(lines, range.0, from, to));
+    for cell in (range.0 + 1)..=range.1 {
+        let overlap = cell_overlap(lines, cell, from, to);
+        if overlap > largest_overlap {
+            largest = cell;
+            largest_overlap = overlap;
+        }
+    }
+    largest
+}
+
+/// Removes the first / last cell of a `cell_range` while the range `[from, to]`
+/// overlaps it by less than the `min_overlap`. Never removes the last remaining cell.
+fn trim_cell_range(lines: &[Millimeter], from: Float, to: Float, range: (usize, usize), min_overlap: MinimumOverlap) -> (usize, usize) {
+
+    let is_enough = |cell: usize| {
+        let overlap = cell_overlap(lines, cell, from, to);
+        overlap > 0.0 && match min_overlap {
+            MinimumOverlap::Any => true,
+            MinimumOverlap::Length(length) => overlap >= length.0,
+            MinimumOverlap::Percent(percent) => overlap / (to - from) * 100.0 >= percent,
+        }
+    };
+
+    let (mut first, mut last) = range;
+
+    while first < last && !is_enough(first) {
+        first += 1;
+    }
+
+    while first < last && !is_enough(last) {
+        last -= 1;
+    }
+
+    (first, last)
+}
+
+/// Returns the (column, row) of every cell that the line segment from `a` to `b`
+/// passes through, in order from `a` to `b` (supercover rasterization over the grid lines).
+///
+/// The segment is split at every grid line it crosses - each piece lies completely
+/// within one cell, which is found by looking up the center of the piece. Pieces
+/// outside of the grid are skipped. Since the pieces have a length, cells that the
+/// segment only touches (at a corner or along a grid line) are not returned.
+fn segment_cells(column_lines: &[Millimeter], row_lines: &[Millimeter], a: PagePoint, b: PagePoint) -> Vec<(usize, usize)> {
+
+    let (x0, y0) = (a.x_from_left.0, a.y_from_top.0);
+    let (dx, dy) = (b.x_from_left.0 - x0, b.y_from_top.0 - y0);
+
+    let cell_at = |x: Float, y: Float| {
+        match (cell_range(column_lines, x, x), cell_range(row_lines, y, y)) {
+            (Some((column, _)), Some((row, _))) => Some((column, row)),
+            _ => None,
+        }
+    };
+
+    if dx == 0.0 && dy == 0.0 {
+        return cell_at(x0, y0).into_iter().collect();
+    }
+
+    // Where (from 0.0 = a to 1.0 = b) does the segment cross a grid line?
+    let crossings = |lines: &[Millimeter], start: Float, delta: Float| {
+        lines.iter()
+        .filter(|_| delta != 0.0)
+        .map(|line| (line.0 - start) / delta)
+        .filter(|t| *t > 0.0 && *t < 1.0)
+        .collect::<Vec<Float>>()
+    };
+
+    let mut splits = vec![0.0, 1.0];
+    splits.extend(crossings(column_lines, x0, dx));
+    splits.extend(crossings(row_lines, y0, dy));
+    splits.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    splits.dedup();
+
+    let mut cells = Vec::new();
+    for piece in splits.windows(2) {
+        let t = (piece[0] + piece[1]) / 2.0;
+        if let Some(cell) = cell_at(x0 + t * dx, y0 + t * dy) {
+            if cells.last() != Some(&cell) {
+                cells.push(cell);
+            }
+        }
+    }
+
+    cells
+}
+
+#[test]
+fn test_insert_street_explicit_lines() {
+    let mm = |v: &[Float]| v.iter().map(|v| Millimeter(*v)).collect::<Vec<_>>();
+    let mut grid = Grid::from_lines(mm(&[0.0, 50.0, 100.0, 130.0]), mm(&[0.0, 40.0, 60.0]), GridConfig::default());
+
+    assert_eq!(grid.bbox.width, Millimeter(130.0));
+    assert_eq!(grid.bbox.height, Millimeter(60.0));
+
+    // spans the narrow last column, in the second (narrow) row
+    grid.insert_street(StreetNameRect {
+        street_name: String::from("Canterbury Road"),
+        x_from_left: Millimeter(90.0),
+        y_from_top: Millimeter(45.0),
+        width: Millimeter(20.0),
+        height: Millimeter(5.0),
+        category: Category::Street,
+        locality: None,
+        index_name: None,
+        fragments: Vec::new(),
+        alternate_names: Vec::new(),
+    });
+
+    // completely outside of the grid
+    grid.insert_street(StreetNameRect {
+        street_name: String::from("Valley View Road"),
+        x_from_left: Millimeter(140.0),
+        y_from_top: Millimeter(10.0),
+        width: Millimeter(20.0),
+        height: Millimeter(5.0),
+        category: Category::Street,
+        locality: None,
+        index_name: None,
+        fragments: Vec::new(),
+        alternate_names: Vec::new(),
+    });
+
+    let positions = grid.street_names().into_iter().map(|s| format!("{}", s.position)).collect::<Vec<_>>();
+    assert_eq!(positions, vec![String::from("B2"), String::from("C2")]);
+}
+
+#[test]
+fn test_insert_street_points() {
+    use units::{Inch, Point};
+
+    let mut grid = Grid::new(Bbox::new(Inch(4.0), Inch(4.0)), GridConfig::with_cell_size(Inch(2.0), Inch(2.0)));
+
+    // 1.5 - 2.5 inch horizontally, 0.5 - 1 inch vertically
+    grid.insert_street(StreetNameRect::new("Canterbury Road", Point(108.0), Point(36.0), Point(72.0), Point(36.0)));
+
+    let positions = grid.street_names().into_iter().map(|s| format!("{}", s.position)).collect::<Vec<_>>();
+    assert_eq!(positions, vec![String::from("A1"), String::from("B1")]);
+}
+
+#[test]
+fn test_uniform_grid_lines() {
+    use self::PartialCellPolicy::*;
+
+    let mm = |v: &[Float]| v.iter().map(|v| Millimeter(*v)).collect::<Vec<_>>();
+
+    // page is a multiple of the cell size, policy doesn't matter
+    for policy in &[Keep, Merge, Ignore] {
+        assert_eq!(uniform_grid_lines(Millimeter(100.0), Millimeter(50.0), *policy), mm(&[0.0, 50.0, 100.0]));
+    }
+
+    assert_eq!(uniform_grid_lines(Millimeter(110.0), Millimeter(50.0), Keep), mm(&[0.0, 50.0, 100.0, 110.0]));
+    assert_eq!(uniform_grid_lines(Millimeter(110.0), Millimeter(50.0), Merge), mm(&[0.0, 50.0, 110.0]));
+    assert_eq!(uniform_grid_lines(Millimeter(110.0), Millimeter(50.0), Ignore), mm(&[0.0, 50.0, 100.0]));
+
+    // page is smaller than one cell: there is nothing to merge into / ignore
+    assert_eq!(uniform_grid_lines(Millimeter(30.0), Millimeter(50.0), Merge), mm(&[0.0, 30.0]));
+    assert_eq!(uniform_grid_lines(Millimeter(30.0), Millimeter(50.0), Ignore), mm(&[0.0, 30.0]));
+
+    // empty page: one empty cell, streets are not indexed
+    assert_eq!(uniform_grid_lines(Millimeter(0.0), Millimeter(50.0), Keep), mm(&[0.0, 0.0]));
+    assert_eq!(uniform_grid_lines(Millimeter(-10.0), Millimeter(50.0), Merge), mm(&[0.0, 0.0]));
+    assert_eq!(uniform_grid_lines(Millimeter(Float::INFINITY), Millimeter(50.0), Keep), mm(&[0.0, 0.0]));
+    assert_eq!(uniform_grid_lines(Millimeter(Float::NAN), Millimeter(50.0), Keep), mm(&[0.0, 0.0]));
+
+    // invalid cells: one cell over the whole page
+    for cell_size in &[0.0, -5.0, Float::INFINITY, Float::NAN] {
+        assert_eq!(uniform_grid_lines(Millimeter(100.0), Millimeter(*cell_size), Merge), mm(&[0.0, 100.0]));
+    }
+    // tiny cells are enlarged
+    let lines = uniform_grid_lines(Millimeter(100.0), Millimeter(0.000_001), Keep);
+    assert_eq!(lines.len(), MAX_UNIFORM_CELLS + 1);
+    assert_eq!(lines[lines.len() - 1], Millimeter(100.0));
+    let mut grid = Grid::new(Bbox::new(Millimeter(0.0), Millimeter(0.0)), GridConfig::default());
+    grid.insert_street(StreetNameRect::new("Canterbury Road", Millimeter(10.0), Millimeter(10.0), Millimeter(5.0), Millimeter(3.0)));
+    assert!(grid.street_names().is_empty());
+}
+
+#[test]
+fn test_cell_boundary_rounding() {
+    let mm = |v: &[Float]| v.iter().map(|v| Millimeter(*v)).collect::<Vec<_>>();
+
+    // 3 * 0.2 != 0.6 in floating point, but this is not a partial cell
+    let lines = uniform_grid_lines(Millimeter(0.6), Millimeter(0.2), PartialCellPolicy::Merge);
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[3], Millimeter(0.6));
+
+    // large-format wall map, 2 x 2 meter with 5 cm cells
+    let lines = uniform_grid_lines(Millimeter(2000.0), Millimeter(50.0), PartialCellPolicy::Keep);
+    assert_eq!(lines.len(), 41);
+    assert_eq!(lines[39], Millimeter(1950.0));
+
+    // exactly on a line = next cell, last line = last cell
+    let lines = mm(&[0.0, 50.0, 100.0]);
+    assert_eq!(cell_range(&lines, 0.0, 50.0), Some((0, 1)));
+    assert_eq!(cell_range(&lines, 49.99, 99.99), Some((0, 1)));
+    assert_eq!(cell_range(&lines, 50.0, 100.0), Some((1, 1)));
+    assert_eq!(cell_range(&lines, 100.0, 120.0), Some((1, 1)));
+    assert_eq!(cell_range(&lines, 100.01, 120.0), None);
+    assert_eq!(cell_range(&lines, -10.0, -0.01), None);
+}
+
+#[test]
+fn test_minimum_overlap() {
+    let bbox = Bbox { width: Millimeter(100.0), height: Millimeter(100.0) };
+    let streets = |min_overlap| {
+        let mut grid = Grid::new(bbox, GridConfig { min_overlap, .. Default::default() });
+        // right edge exactly on the line at 50 mm
+        grid.insert_street(StreetNameRect::new("Canterbury Road", Millimeter(10.0), Millimeter(10.0), Millimeter(40.0), Millimeter(5.0)));
+        // descender pokes 0.5 mm into the second row
+        grid.insert_street(StreetNameRect::new("Valley View Road", Millimeter(10.0), Millimeter(46.0), Millimeter(30.0), Millimeter(4.5)));
+        // 25% in the first column, 75% in the second one
+        grid.insert_street(StreetNameRect::new("Mayer Street", Millimeter(40.0), Millimeter(60.0), Millimeter(40.0), Millimeter(5.0)));
+        grid.street_names().into_iter().map(|s| format!("{} {}", s.street_name, s.position)).collect::<Vec<_>>()
+    };
+
+    assert_eq!(streets(MinimumOverlap::Any), vec![
+        "Canterbury Road A1", "Valley View Road A1", "Valley View Road A2", "Mayer Street A2", "Mayer Street B2",
+    ]);
+    assert_eq!(streets(MinimumOverlap::Length(Millimeter(1.0))), vec![
+        "Canterbury Road A1", "Valley View Road A1", "Mayer Street A2", "Mayer Street B2",
+    ]);
+    assert_eq!(streets(MinimumOverlap::Percent(30.0)), vec![
+        "Canterbury Road A1", "Valley View Road A1", "Mayer Street B2",
+    ]);
+}
+
+#[test]
+fn test_assignment_strategy() {
+    let bbox = Bbox { width: Millimeter(150.0), height: Millimeter(100.0) };
+    let positions = |assignment| {
+        let mut grid = Grid::new(bbox, GridConfig { assignment, .. Default::default() });
+        // covers A1 - C2, mostly in B1
+        grid.insert_street(StreetNameRect::new("Canterbury Road", Millimeter(45.0), Millimeter(10.0), Millimeter(60.0), Millimeter(45.0)));
+        grid.street_names().into_iter().map(|s| format!("{}", s.position)).collect::<Vec<_>>()
+    };
+
+    assert_eq!(positions(AssignmentStrategy::Corners), vec!["A1", "A2", "C1", "C2"]);
+    assert_eq!(positions(AssignmentStrategy::FullCoverage), vec!["A1", "A2", "B1", "B2", "C1", "C2"]);
+    assert_eq!(positions(AssignmentStrategy::Center), vec!["B1"]);
+    assert_eq!(positions(AssignmentStrategy::MajorityArea), vec!["B1"]);
+}
+
+#[test]
+fn test_insert_road_line() {
+    let mut grid = Grid::new(Bbox::new(Millimeter(150.0), Millimeter(150.0)), GridConfig::default());
+
+    grid.insert_road_line(RoadLine {
+        street_name: String::from("Canterbury Road"),
+        points: vec![
+            // diagonal from A1 to B2, passing exactly through the corner at (50, 50)
+            PagePoint::new(Millimeter(10.0), Millimeter(10.0)),
+            PagePoint::new(Millimeter(60.0), Millimeter(60.0)),
+            // continues to the right, through C2 and off the page
+            PagePoint::new(Millimeter(200.0), Millimeter(60.0)),
+        ],
+        category: Category::Street,
+        locality: None,
+        alternate_names: Vec::new(),
+    });
+
+    // shallow diagonal: A1, A2, B2 (supercover) - not only A1 and B2
+    grid.insert_road_line(RoadLine {
+        street_name: String::from("Valley View Road"),
+        points: vec![
+            PagePoint::new(Millimeter(10.0), Millimeter(40.0)),
+            PagePoint::new(Millimeter(90.0), Millimeter(70.0)),
+        ],
+        category: Category::Street,
+        locality: None,
+        alternate_names: Vec::new(),
+    });
+
+    // along the line between the columns A and B: only B1 and B2,
+    // along the right edge of the page: the last column
+    let line = |street_name: &str, x: Float| RoadLine {
+        street_name: String::from(street_name),
+        points: vec![PagePoint::new(Millimeter(x), Millimeter(10.0)), PagePoint::new(Millimeter(x), Millimeter(60.0))],
+        category: Category::Street,
+        locality: None,
+        alternate_names: Vec::new(),
+    };
+    grid.insert_road_line(line("Mill Lane", 50.0));
+    grid.insert_road_line(line("Ring Road", 150.0));
+
+    let positions = grid.street_names().into_iter().map(|s| format!("{} {}", s.street_name, s.position)).collect::<Vec<_>>();
+    assert_eq!(positions, vec![
+        "Canterbury Road A1", "Canterbury Road B2", "Canterbury Road C2",
+        "Valley View Road A1", "Valley View Road A2", "Valley View Road B2",
+        "Mill Lane B1", "Mill Lane B2",
+        "Ring Road C1", "Ring Road C2",
+    ]);
+}
+
+#[test]
+fn test_insert_area() {
+    let mut grid = Grid::new(Bbox::new(Millimeter(100.0), Millimeter(100.0)), GridConfig::default());
+    let park = vec![
+        PagePoint::new(Millimeter(10.0), Millimeter(10.0)),
+        PagePoint::new(Millimeter(60.0), Millimeter(10.0)),
+        PagePoint::new(Millimeter(60.0), Millimeter(60.0)),
+        PagePoint::new(Millimeter(10.0), Millimeter(60.0)),
+    ];
+
+    // A1 is covered by 64%, B1 and A2 by 16%, B2 by 4%
+    let area = |name: &str, reference| AreaFeature { name: String::from(name), outline: park.clone(), reference, category: Category::Park };
+    grid.insert_area(area("Central Park", AreaReference::Coverage(10.0)));
+    grid.insert_area(area("City Park", AreaReference::Coverage(0.0)));
+    grid.insert_area(area("Hyde Park", AreaReference::Label(PageRect::new(Millimeter(70.0), Millimeter(70.0), Millimeter(20.0), Millimeter(5.0)))));
+
+    let positions = grid.street_names().into_iter().map(|s| format!("{} {}", s.street_name, s.position)).collect::<Vec<_>>();
+    assert_eq!(positions, vec![
+        "Central Park A1", "Central Park A2", "Central Park B1",
+        "City Park A1", "City Park A2", "City Park B1", "City Park B2",
+        "Hyde Park B2",
+    ]);
+}
+
+#[test]
+fn test_insert_point() {
+    let mut grid = Grid::new(Bbox::new(Millimeter(100.0), Millimeter(100.0)), GridConfig::default());
+    let point = |name: &str, symbol_radius| PointFeature {
+        name: String::from(name),
+        position: PagePoint::new(Millimeter(48.0), Millimeter(20.0)),
+        symbol_radius,
+        symbol: Some(String::from("rail")),
+        category: Category::PointOfInterest,
+    };
+
+    grid.insert_point(point("Central Station", None));
+    // the symbol reaches into the next column
+    grid.insert_point(point("North Station", Some(Millimeter(3.0))));
+
+    let positions = grid.street_names().into_iter().map(|s| format!("{} {}", s.index_name(), s.position)).collect::<Vec<_>>();
+    assert_eq!(positions, vec![
+        "Central Station \u{27E8}rail\u{27E9} A1",
+        "North Station \u{27E8}rail\u{27E9} A1",
+        "North Station \u{27E8}rail\u{27E9} B1",
+    ]);
+}
+
+#[test]
+fn test_district_locality() {
+    use roads2csv::DeduplicatedRoads;
+
+    let mut grid = Grid::new(Bbox::new(Millimeter(100.0), Millimeter(100.0)), GridConfig::default());
+    let district = |name: &str, left: Float, right: Float| District {
+        name: String::from(name),
+        outline: vec![
+            PagePoint::new(Millimeter(left), Millimeter(0.0)),
+            PagePoint::new(Millimeter(right), Millimeter(0.0)),
+            PagePoint::new(Millimeter(right), Millimeter(100.0)),
+            PagePoint::new(Millimeter(left), Millimeter(100.0)),
+        ],
+    };
+
+    grid.add_district(district("Altdorf", 0.0, 40.0));
+    grid.add_district(district("Neudorf", 40.0, 100.0));
+
+    // mostly in Altdorf
+    grid.insert_street(StreetNameRect::new("Hauptstraße", Millimeter(10.0), Millimeter(10.0), Millimeter(35.0), Millimeter(5.0)));
+    // mostly in Neudorf
+    grid.insert_street(StreetNameRect::new("Hauptstraße", Millimeter(30.0), Millimeter(60.0), Millimeter(35.0), Millimeter(5.0)));
+    // explicit locality wins
+    grid.insert_street(StreetNameRect { locality: Some(String::from("Altdorf")), .. StreetNameRect::new("Kirchweg", Millimeter(60.0), Millimeter(10.0), Millimeter(10.0), Millimeter(5.0)) });
+
+    let positions = grid.street_names().into_iter().map(|s| format!("{} {}", s.index_name(), s.position)).collect::<Vec<_>>();
+    assert_eq!(positions, vec![
+        "Hauptstraße (Altdorf) A1",
+        "Hauptstraße (Neudorf) A2",
+        "Hauptstraße (Neudorf) B2",
+        "Kirchweg (Altdorf) B1",
+    ]);
+
+    // The address at the left end of the second label lies in Altdorf,
+    // but belongs to the road in Neudorf at the same cell
+    let address = |house_number: &str, x: Float| AddressPoint {
+        street_name: String::from("Hauptstraße"),
+        house_number: String::from(house_number),
+        position: PagePoint::new(Millimeter(x), Millimeter(62.0)),
+        locality: None,
+    };
+    grid.insert_address(address("1", 32.0));
+    grid.insert_address(address("9", 62.0));
+
+    let mut roads = DeduplicatedRoads::from_streets(&grid.street_names());
+    roads.add_house_numbers(&grid.addresses());
+    let (processed, _) = roads.process();
+    assert_eq!(processed.to_csv("\t"), "Hauptstraße (Altdorf)\tA1\r\nHauptstraße (Neudorf)\t1 A2, 9 B2\r\nKirchweg (Altdorf)\tB1");
+}
+
+#[test]
+fn test_index_name() {
+    let mut grid = Grid::new(Bbox::new(Millimeter(100.0), Millimeter(100.0)), GridConfig::default());
+    grid.set_abbreviations(AbbreviationExpander::german());
+
+    grid.insert_street(StreetNameRect::new("Kaiser-Wilhelm-Straße", Millimeter(10.0), Millimeter(10.0), Millimeter(30.0), Millimeter(5.0)));
+    grid.insert_street(StreetNameRect::new("Kaiser-Wilh.-Str.", Millimeter(60.0), Millimeter(10.0), Millimeter(20.0), Millimeter(5.0)));
+    grid.insert_street(StreetNameRect {
+        index_name: Some(String::from("Kaiser-Wilhelm-Straße")),
+        .. StreetNameRect::new("K.-W.-Str.", Millimeter(60.0), Millimeter(60.0), Millimeter(10.0), Millimeter(5.0))
+    });
+
+    let deduplicated = ::roads2csv::DeduplicatedRoads::from_streets(&grid.street_names());
+    let (processed, unprocessed) = deduplicated.process();
+    assert!(processed.processed.is_empty());
+    assert_eq!(unprocessed.to_csv("\t"), "Kaiser-Wilhelm-Straße\tA1\tB1\tB2");
+}
+
+#[test]
+fn test_merge_label_fragments() {
+    let label = |name: &str, x: Float, y: Float| StreetNameRect::new(name, Millimeter(x), Millimeter(y), Millimeter(20.0), Millimeter(4.0));
+
+    let merged = merge_label_fragments(vec![
+        label("Friedrich-Ebert-", 25.0, 10.0),
+        label("Canter-", 10.0, 70.0),
+        label("Straße", 35.0, 15.0),
+        label("bury Road", 12.0, 75.0),
+        // too far away from "Canter-"
+        label("Mayer Street", 10.0, 90.0),
+    ], Millimeter(3.0));
+
+    let names = merged.iter().map(|l| l.street_name.clone()).collect::<Vec<_>>();
+    assert_eq!(names, vec!["Friedrich-Ebert-Straße", "Canterbury Road", "Mayer Street"]);
+
+    // "Friedrich-Ebert-" is in A1, "Straße" reaches into B1
+    let mut grid = Grid::new(Bbox::new(Millimeter(100.0), Millimeter(100.0)), GridConfig::default());
+    grid.insert_street(merged[0].clone());
+    let positions = grid.street_names().into_iter().map(|s| format!("{} {}", s.street_name, s.position)).collect::<Vec<_>>();
+    assert_eq!(positions, vec!["Friedrich-Ebert-Straße A1", "Friedrich-Ebert-Straße B1"]);
+
+    // ... but as a single label, it's only assigned to A1
+    let mut grid = Grid::new(Bbox::new(Millimeter(100.0), Millimeter(100.0)), GridConfig { assignment: AssignmentStrategy::MajorityArea, .. Default::default() });
+    grid.insert_street(merged[0].clone());
+    let positions = grid.street_names().into_iter().map(|s| format!("{}", s.position)).collect::<Vec<_>>();
+    assert_eq!(positions, vec!["A1"]);
+}
+
+#[test]
+fn test_insert_address() {
+    use roads2csv::DeduplicatedRoads;
+
+    let mut grid = Grid::new(Bbox::new(Millimeter(100.0), Millimeter(100.0)), GridConfig::default());
+    grid.set_abbreviations(AbbreviationExpander::english());
+    grid.insert_street(StreetNameRect::new("Canterbury Rd", Millimeter(30.0), Millimeter(60.0), Millimeter(40.0), Millimeter(4.0)));
+
+    let address = |house_number: &str, x: Float| AddressPoint {
+        street_name: String::from("Canterbury Rd"),
+        house_number: String::from(house_number),
+        position: PagePoint::new(Millimeter(x), Millimeter(65.0)),
+        locality: None,
+    };
+    grid.insert_address(address("1", 10.0));
+    grid.insert_address(address("47a", 45.0));
+    grid.insert_address(address("48", 55.0));
+    grid.insert_address(address("120", 90.0));
+    // no number, outside of the grid
+    grid.insert_address(address("n/a", 20.0));
+    grid.insert_address(address("122", 120.0));
+
+    let mut roads = DeduplicatedRoads::from_streets(&grid.street_names());
+    roads.add_house_numbers(&grid.addresses());
+    let (processed, _) = roads.process();
+    assert_eq!(processed.to_csv("\t"), "Canterbury Road\t1-47 A2, 48-120 B2");
+}
+
+#[test]
+fn test_partial_cell_count() {
+    let bbox = Bbox { width: Millimeter(110.0), height: Millimeter(100.0) };
+    let config = |partial_cells| GridConfig { cell_width: Millimeter(50.0), cell_height: Millimeter(50.0), partial_cells, .. Default::default() };
+
+    let grid = Grid::new(bbox, config(PartialCellPolicy::Keep));
+    assert_eq!((grid.column_count(), grid.row_count()), (3, 2));
+    let grid = Grid::new(bbox, config(PartialCellPolicy::Merge));
+    assert_eq!((grid.column_count(), grid.row_count()), (2, 2));
+
+    let mut grid = Grid::new(bbox, config(PartialCellPolicy::Ignore));
+    assert_eq!((grid.column_count(), grid.row_count()), (2, 2));
+    // street name in the ignored sliver is not indexed
+    grid.insert_street(StreetNameRect {
+        street_name: String::from("Canterbury Road"),
+        x_from_left: Millimeter(102.0),
+        y_from_top: Millimeter(10.0),
+        width: Millimeter(5.0),
+        height: Millimeter(3.0),
+        category: Category::Street,
+        locality: None,
+        index_name: None,
+        fragments: Vec::new(),
+        alternate_names: Vec::new(),
+    });
+    assert!(grid.street_names().is_empty());
 }
 
 /// Maps an index number to a value, necessary for creating the street index. i.e.:
@@ -232,8 +1538,27 @@ pub fn number_to_alphabet_value(num: usize) -> String {
     unsafe { ::std::str::from_utf8_unchecked(slice) }.to_string()
 }
 
+/// Reverse of `number_to_alphabet_value`: `"A"` -> 0, `"AA"` -> 26.
+/// Returns `None` if the value is empty or not in uppercase letters.
+pub fn alphabet_value_to_number(value: &str) -> Option<usize> {
+    if value.is_empty() {
+        return None;
+    }
+
+    let mut num: usize = 0;
+    for c in value.bytes() {
+        if !c.is_ascii_uppercase() {
+            return None;
+        }
+        num = num.checked_mul(26)?.checked_add((c - b'A') as usize + 1)?;
+    }
+
+    Some(num - 1)
+}
+
 // Transform from 0 to A, 1 to B, etc.
 #[inline(always)]
+#[allow(clippy::char_lit_as_u8)]
 fn u8_to_char(input: u8) -> u8 {
     // use 'a' as u8 to create lowercase characters
     'A' as u8 + input
@@ -249,6 +1574,16 @@ fn test_number_to_alphabet_value() {
     assert_eq!(number_to_alphabet_value(225), String::from("HR"));
 }
 
+#[test]
+fn test_alphabet_value_to_number() {
+    assert_eq!(alphabet_value_to_number("A"), Some(0));
+    assert_eq!(alphabet_value_to_number("HR"), Some(225));
+    for num in &[0, 25, 26, 27, 225, 80000] {
+        assert_eq!(alphabet_value_to_number(&number_to_alphabet_value(*num)), Some(*num));
+    }
+    assert_eq!(alphabet_value_to_number("a"), None);
+}
+
 #[cfg(all(test, feature = "nightly"))]
 mod tests {
     use super::*;
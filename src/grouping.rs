@@ -0,0 +1,235 @@
+//! Grouping of the sorted index into letter sections (`"A"`, `"B"`, ...)
+//!
+//! Which letter a name is listed under depends on the language: in German,
+//! `"Ärztehaus"` is listed under `"A"`, in Swedish `"Å"`, `"Ä"` and `"Ö"` are
+//! letters of their own after `"Z"`, and traditional Spanish indexes have
+//! sections for `"Ch"` and `"Ll"`. All exporters take a `LetterGrouping`.
+
+use roads2csv::IndexEntry;
+
+/// Letter section of the index, i.e. all entries under `"A"`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexSection<'a> {
+    /// Header of the section: `"A"`, `"Ch"` or `"#"`
+    pub letter: String,
+    pub entries: Vec<&'a IndexEntry>,
+}
+
+/// Rules for the letter sections of an index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LetterGrouping {
+    /// Letters of the alphabet in the order of the sections. A letter can have
+    /// more than one character (`"Ch"`), the longest letter that a name
+    /// starts with is used.
+    pub letters: Vec<String>,
+    /// Whether a name that starts with an accented letter which isn't in the
+    /// `letters` is listed under the letter without the accent (`"Ärztehaus"`
+    /// under `"A"`). Otherwise, or if the letter has no accent (`"Ø"`), it gets
+    /// a section of its own after the letters.
+    pub fold_accents: bool,
+    /// Header of the section of names starting with a digit, before the letters
+    pub digits: String,
+}
+
+impl LetterGrouping {
+    /// Sections A-Z, accented letters under the letter without the accent,
+    /// names starting with a digit under `"#"`
+    pub fn new() -> Self {
+        Self {
+            letters: (b'A'..=b'Z').map(|c| (c as char).to_string()).collect(),
+            fold_accents: true,
+            digits: String::from("#"),
+        }
+    }
+
+    /// German: `"Ä"`, `"Ö"` and `"Ü"` are listed under `"A"`, `"O"` and `"U"`
+    pub fn german() -> Self {
+        Self::new()
+    }
+
+    /// Swedish: `"Å"`, `"Ä"` and `"Ö"` are letters of their own after `"Z"`
+    pub fn swedish() -> Self {
+        let mut grouping = Self::new();
+        grouping.letters.extend(["Å", "Ä", "Ö"].iter().map(|letter| letter.to_string()));
+        grouping
+    }
+
+    /// Spanish: `"Ñ"` is a letter of its own after `"N"`
+    pub fn spanish() -> Self {
+        Self::new().with_letter("Ñ", "N")
+    }
+
+    /// Traditional Spanish: `"Ch"`, `"Ll"` and `"Ñ"` are letters of their own
+    /// after `"C"`, `"L"` and `"N"`
+    pub fn spanish_traditional() -> Self {
+        Self::spanish().with_letter("Ch", "C").with_letter("Ll", "L")
+    }
+
+    /// Inserts a letter into the alphabet after the letter `after`
+    /// (or at the end, if `after` is not in the alphabet)
+    pub fn with_letter(mut self, letter: &str, after: &str) -> Self {
+        let index = self.letters.iter().position(|l| l == after).map(|i| i + 1).unwrap_or(self.letters.len());
+        self.letters.insert(index, letter.to_string());
+        self
+    }
+
+    /// Returns the header of the section that the name is listed under
+    pub fn letter(&self, name: &str) -> String {
+        let start = match name.char_indices().find(|(_, c)| c.is_alphanumeric()) {
+            Some((_, c)) if c.is_numeric() => return self.digits.clone(),
+            Some((start, _)) => start,
+            None => return self.digits.clone(),
+        };
+        let name = name[start..].to_uppercase();
+
+        let longest = self.letters.iter()
+            .filter(|letter| !letter.is_empty() && name.starts_with(&letter.to_uppercase()))
+            .max_by_key(|letter| letter.chars().count());
+        if let Some(letter) = longest {
+            return letter.clone();
+        }
+
+        let first = name.chars().next().unwrap_or(' ');
+        if self.fold_accents {
+            if let Some(letter) = base_letter(first).and_then(|base| self.letters.iter().find(|letter| **letter == base.to_string())) {
+                return letter.clone();
+            }
+        }
+        first.to_string()
+    }
+
+    /// Returns the key for sorting a name in the order of the alphabet: the
+    /// `letters` by their position in the alphabet (at each position the longest
+    /// letter, so that `"Ch"` sorts after `"Cz"`), accented letters that aren't
+    /// in the `letters` like the letter without the accent (if `fold_accents`),
+    /// other letters after the alphabet. Digits come before the letters, spaces
+    /// and punctuation before everything else. Upper and lower case are equal.
+    pub fn collation_key(&self, name: &str) -> Vec<u32> {
+        const DIGITS: u32 = 1;
+        const LETTERS: u32 = DIGITS + 10;
+
+        let letters = self.letters.iter().map(|letter| letter.to_uppercase()).collect::<Vec<String>>();
+        let name = name.to_uppercase();
+        let mut rest = name.as_str();
+        let mut key = Vec::new();
+
+        while let Some(c) = rest.chars().next() {
+            let longest = letters.iter().enumerate()
+                .filter(|(_, letter)| !letter.is_empty() && rest.starts_with(letter.as_str()))
+                .max_by_key(|(_, letter)| letter.len());
+            if let Some((index, letter)) = longest {
+                key.push(LETTERS + index as u32);
+                rest = &rest[letter.len()..];
+                continue;
+            }
+
+            let folded = if self.fold_accents {
+                base_letter(c).and_then(|base| letters.iter().position(|letter| *letter == base.to_string()))
+            } else {
+                None
+            };
+            key.push(match (folded, c.to_digit(10)) {
+                (Some(index), _) => LETTERS + index as u32,
+                (None, Some(digit)) => DIGITS + digit,
+                (None, None) if c.is_alphanumeric() => LETTERS + letters.len() as u32 + c as u32,
+                (None, None) => 0,
+            });
+            rest = &rest[c.len_utf8()..];
+        }
+
+        key
+    }
+
+    /// Sorts the entries alphabetically by the `collation_key` of their names.
+    /// Entries with the same key keep their order.
+    pub fn sort_entries(&self, entries: &mut [IndexEntry]) {
+        entries.sort_by_cached_key(|entry| self.collation_key(&entry.name.to_string()));
+    }
+
+    /// Splits the (sorted) entries into letter sections: the digits first, then
+    /// the letters in the order of the alphabet, then the letters that are not in
+    /// the alphabet. Within a section, the entries keep the order of the index
+    /// (use `sort_entries` before for the order of the alphabet).
+    pub fn sections<'a>(&self, entries: &'a [IndexEntry]) -> Vec<IndexSection<'a>> {
+        let mut sections: Vec<IndexSection<'a>> = Vec::new();
+        for entry in entries {
+            let letter = self.letter(&entry.name.street_name.0);
+            match sections.iter_mut().find(|section| section.letter == letter) {
+                Some(section) => section.entries.push(entry),
+                None => sections.push(IndexSection { letter, entries: vec![entry] }),
+            }
+        }
+
+        // Stable, so the letters that are not in the alphabet stay in the order they appear
+        sections.sort_by_key(|section| {
+            if section.letter == self.digits {
+                0
+            } else {
+                self.letters.iter().position(|letter| *letter == section.letter).map(|i| i + 1).unwrap_or(self.letters.len() + 1)
+            }
+        });
+        sections
+    }
+}
+
+impl Default for LetterGrouping {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Letter without the accent, i.e. `'a'` for `'ä'`
+pub(crate) fn base_letter(c: char) -> Option<char> {
+    const BASES: &[(&str, char)] = &[
+        ("àáâãäåăāą", 'a'), ("ÀÁÂÃÄÅĂĀĄ", 'A'),
+        ("ćçč", 'c'), ("ĆÇČ", 'C'), ("ď", 'd'), ("Ď", 'D'),
+        ("èéêëěēę", 'e'), ("ÈÉÊËĚĒĘ", 'E'), ("ğ", 'g'), ("Ğ", 'G'),
+        ("ìíîïī", 'i'), ("ÌÍÎÏĪ", 'I'), ("ĺľ", 'l'), ("ĹĽ", 'L'),
+        ("ñńň", 'n'), ("ÑŃŇ", 'N'), ("òóôõöőō", 'o'), ("ÒÓÔÕÖŐŌ", 'O'),
+        ("ŕř", 'r'), ("ŔŘ", 'R'), ("śşš", 's'), ("ŚŞŠ", 'S'), ("ţť", 't'), ("ŢŤ", 'T'),
+        ("ùúûüűůū", 'u'), ("ÙÚÛÜŰŮŪ", 'U'), ("ýÿ", 'y'), ("ÝŸ", 'Y'),
+        ("źżž", 'z'), ("ŹŻŽ", 'Z'),
+    ];
+    BASES.iter().find(|(accented, _)| accented.contains(c)).map(|(_, base)| *base)
+}
+
+#[test]
+fn test_letter_grouping() {
+    let entry = |name: &str| IndexEntry::new(name, "A1");
+    let entries = ["1. Mai-Straße", "Åsgatan", "Ängsvägen", "Calle Mayor", "Chorrillo", "Llano", "Luna", "Zoo", "Ærøvej", "Ängsvägen 2"]
+        .iter().map(|name| entry(name)).collect::<Vec<_>>();
+    let letters = |grouping: LetterGrouping| grouping.sections(&entries).iter()
+        .map(|section| format!("{}:{}", section.letter, section.entries.len()))
+        .collect::<Vec<String>>();
+
+    assert_eq!(letters(LetterGrouping::german()), vec!["#:1", "A:3", "C:2", "L:2", "Z:1", "Æ:1"]);
+    assert_eq!(letters(LetterGrouping::swedish()), vec!["#:1", "C:2", "L:2", "Z:1", "Å:1", "Ä:2", "Æ:1"]);
+    assert_eq!(letters(LetterGrouping::spanish_traditional()), vec!["#:1", "A:3", "C:1", "Ch:1", "L:1", "Ll:1", "Z:1", "Æ:1"]);
+
+    // The order of the index is kept within a section (i.e. sorted by position) ...
+    let german = LetterGrouping::german();
+    let names = |entries: &[IndexEntry]| german.sections(entries)[1].entries.iter().map(|entry| entry.name.to_string()).collect::<Vec<_>>();
+    assert_eq!(names(&entries), vec!["Åsgatan", "Ängsvägen", "Ängsvägen 2"]);
+    // ... unless the entries are sorted alphabetically first
+    let mut sorted = entries.clone();
+    german.sort_entries(&mut sorted);
+    assert_eq!(names(&sorted), vec!["Ängsvägen", "Ängsvägen 2", "Åsgatan"]);
+    assert_eq!(german.letter("\"Zur Post\""), "Z");
+    assert_eq!(german.letter("élysée"), "E");
+}
+
+#[test]
+fn test_collation_key() {
+    let sorted = |grouping: LetterGrouping, names: &[&'static str]| {
+        let mut names = names.to_vec();
+        names.sort_by_key(|name| grouping.collation_key(name));
+        names
+    };
+
+    assert_eq!(sorted(LetterGrouping::german(), &["Azalea Weg", "Ärztehaus", "Abbey Road", "ärztehaus 2"]), vec!["Abbey Road", "Ärztehaus", "ärztehaus 2", "Azalea Weg"]);
+    assert_eq!(sorted(LetterGrouping::swedish(), &["Östra vägen", "Zinkgatan", "Åsgatan", "Ängsvägen"]), vec!["Zinkgatan", "Åsgatan", "Ängsvägen", "Östra vägen"]);
+    assert_eq!(sorted(LetterGrouping::spanish_traditional(), &["Coche", "Cocina", "Llano", "Luna", "Ñu", "Nube"]), vec!["Cocina", "Coche", "Luna", "Llano", "Nube", "Ñu"]);
+    // word by word, digits before letters
+    assert_eq!(sorted(LetterGrouping::new(), &["Millbank", "Mill Lane", "Mill 2", "Mill"]), vec!["Mill", "Mill 2", "Mill Lane", "Millbank"]);
+}
+
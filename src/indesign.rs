@@ -0,0 +1,171 @@
+//! Export of the index for Adobe InDesign
+//!
+//! Instead of pasting the CSV into a text frame and formatting it by hand,
+//! the index is exported with named paragraph and character styles, either
+//! as InDesign Tagged Text (File > Place) or as an IDML story, so that the
+//! index flows straight into the styles of the layout template.
+
+use roads2csv::IndexEntry;
+use units::{Point, Float};
+use layout::escape_xml;
+use grouping::LetterGrouping;
+
+/// Names of the styles that are applied to the index and the tab stop of the entries
+#[derive(Debug, Clone, PartialEq)]
+pub struct InDesignExport {
+    /// Paragraph style of the letter section headers
+    pub header_style: String,
+    /// Paragraph style of the entries
+    pub entry_style: String,
+    /// Character style of the references
+    pub reference_style: String,
+    /// Position of the right-aligned tab stop between the name and the reference
+    /// (usually the width of the column). If this is `None`, the tab stops of
+    /// the paragraph style in the template are used.
+    pub tab_position: Option<Point>,
+    /// Leader character of the tab stop
+    pub leader: char,
+    /// Letter sections of the index, see `LetterGrouping`
+    pub grouping: LetterGrouping,
+}
+
+impl InDesignExport {
+    /// Styles `"Index Header"`, `"Index Entry"` and `"Index Reference"`,
+    /// with the tab stops and leaders of the template
+    pub fn new() -> Self {
+        Self {
+            header_style: String::from("Index Header"),
+            entry_style: String::from("Index Entry"),
+            reference_style: String::from("Index Reference"),
+            tab_position: None,
+            leader: '.',
+            grouping: LetterGrouping::new(),
+        }
+    }
+
+    /// Exports the (sorted) entries as InDesign Tagged Text. The paragraph styles
+    /// are defined at the start of the text, so the text can also be placed into
+    /// documents that don't have the styles yet.
+    ///
+    /// Use `tagged_text_utf16` for writing the file, InDesign expects
+    /// `<UNICODE-WIN>` text to be encoded as UTF-16.
+    pub fn tagged_text(&self, entries: &[IndexEntry]) -> String {
+        let mut text = String::from("<UNICODE-WIN>\r\n<Version:8><FeatureSet:InDesign-Roman>");
+
+        let tab_ruler = match self.tab_position {
+            Some(position) => format!("<pTabRuler:{}\\,Right\\,.\\,0\\,{}\\;>", tagged_number(position.0), escape_tagged_text(&self.leader.to_string())),
+            None => String::new(),
+        };
+        text.push_str(&format!("<DefineParaStyle:{}=<Nextstyle:{}>>", escape_tagged_text(&self.header_style), escape_tagged_text(&self.entry_style)));
+        text.push_str(&format!("<DefineParaStyle:{}=<Nextstyle:{}>{}>", escape_tagged_text(&self.entry_style), escape_tagged_text(&self.entry_style), tab_ruler));
+        text.push_str(&format!("<DefineCharStyle:{}=>", escape_tagged_text(&self.reference_style)));
+
+        for section in self.grouping.sections(entries) {
+            text.push_str(&format!("\r\n<ParaStyle:{}>{}", escape_tagged_text(&self.header_style), escape_tagged_text(&section.letter)));
+            for entry in section.entries {
+                text.push_str(&format!("\r\n<ParaStyle:{}>{}\t<CharStyle:{}>{}<CharStyle:>",
+                    escape_tagged_text(&self.entry_style),
+                    escape_tagged_text(&entry.name.to_string()),
+                    escape_tagged_text(&self.reference_style),
+                    escape_tagged_text(&entry.reference)));
+            }
+        }
+
+        text
+    }
+
+    /// Returns the `tagged_text` encoded as UTF-16 (little endian, with BOM),
+    /// ready to be written to a `.txt` file
+    pub fn tagged_text_utf16(&self, entries: &[IndexEntry]) -> Vec<u8> {
+        let mut bytes = vec![0xFF, 0xFE];
+        for unit in self.tagged_text(entries).encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Exports the (sorted) entries as the story XML of an IDML package
+    /// (`Stories/Story_<story_id>.xml`). The styles are not defined in the story,
+    /// so they have to exist in the `Styles.xml` of the template.
+    pub fn idml_story(&self, entries: &[IndexEntry], story_id: &str) -> String {
+        let paragraph = |style: &str, tab_list: &str, content: &str| format!(
+            "<ParagraphStyleRange AppliedParagraphStyle=\"ParagraphStyle/{}\">{}{}<Br/></ParagraphStyleRange>",
+            escape_xml(style), tab_list, content);
+        let characters = |style: &str, text: &str| format!(
+            "<CharacterStyleRange AppliedCharacterStyle=\"CharacterStyle/{}\"><Content>{}</Content></CharacterStyleRange>",
+            escape_xml(style), escape_xml(text));
+        let no_character_style = "$ID/[No character style]";
+
+        let tab_list = match self.tab_position {
+            Some(position) => format!(
+                "<Properties><TabList type=\"list\"><ListItem type=\"record\"><Alignment type=\"enumeration\">RightAlign</Alignment>\
+                <AlignmentCharacter type=\"string\">.</AlignmentCharacter><Leader type=\"string\">{}</Leader>\
+                <Position type=\"unit\">{}</Position></ListItem></TabList></Properties>",
+                escape_xml(&self.leader.to_string()), tagged_number(position.0)),
+            None => String::new(),
+        };
+
+        let mut story = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+            <idPkg:Story xmlns:idPkg=\"http://ns.adobe.com/AdobeInDesign/idml/1.0/packaging\" DOMVersion=\"8.0\">\n\
+            <Story Self=\"{}\">\n", escape_xml(story_id));
+
+        for section in self.grouping.sections(entries) {
+            story.push_str(&paragraph(&self.header_style, "", &characters(no_character_style, &section.letter)));
+            story.push('\n');
+            for entry in section.entries {
+                let content = characters(no_character_style, &format!("{}\t", entry.name)) + &characters(&self.reference_style, &entry.reference);
+                story.push_str(&paragraph(&self.entry_style, &tab_list, &content));
+                story.push('\n');
+            }
+        }
+
+        story.push_str("</Story>\n</idPkg:Story>\n");
+        story
+    }
+}
+
+impl Default for InDesignExport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Escapes the characters that have a special meaning in Tagged Text
+fn escape_tagged_text(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('<', "\\<").replace('>', "\\>")
+}
+
+/// Formats a length in points with at most 3 decimals
+fn tagged_number(value: Float) -> String {
+    let formatted = format!("{:.3}", value);
+    formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[test]
+fn test_indesign_export() {
+    let entries = [IndexEntry::new("Abbey Road", "B2"), IndexEntry::new("Alte Gasse <Süd>", "C3-C4"), IndexEntry::new("Baker Street", "A1")];
+
+    let export = InDesignExport { tab_position: Some(Point(150.0)), .. InDesignExport::new() };
+    let text = export.tagged_text(&entries);
+    assert_eq!(text.lines().collect::<Vec<_>>(), vec![
+        "<UNICODE-WIN>",
+        "<Version:8><FeatureSet:InDesign-Roman><DefineParaStyle:Index Header=<Nextstyle:Index Entry>>\
+        <DefineParaStyle:Index Entry=<Nextstyle:Index Entry><pTabRuler:150\\,Right\\,.\\,0\\,.\\;>><DefineCharStyle:Index Reference=>",
+        "<ParaStyle:Index Header>A",
+        "<ParaStyle:Index Entry>Abbey Road\t<CharStyle:Index Reference>B2<CharStyle:>",
+        "<ParaStyle:Index Entry>Alte Gasse \\<Süd\\>\t<CharStyle:Index Reference>C3-C4<CharStyle:>",
+        "<ParaStyle:Index Header>B",
+        "<ParaStyle:Index Entry>Baker Street\t<CharStyle:Index Reference>A1<CharStyle:>",
+    ]);
+    assert_eq!(&export.tagged_text_utf16(&entries)[..6], &[0xFF, 0xFE, b'<', 0, b'U', 0]);
+
+    let export = InDesignExport { tab_position: Some(Point(85.5)), leader: '\u{b7}', .. InDesignExport::new() };
+    assert!(export.tagged_text(&entries).contains("<pTabRuler:85.5\\,Right\\,.\\,0\\,\u{b7}\\;>"));
+
+    let story = InDesignExport::new().idml_story(&entries, "uindex");
+    assert!(story.contains("<Story Self=\"uindex\">"));
+    assert!(story.contains("<ParagraphStyleRange AppliedParagraphStyle=\"ParagraphStyle/Index Entry\">\
+        <CharacterStyleRange AppliedCharacterStyle=\"CharacterStyle/$ID/[No character style]\"><Content>Alte Gasse &lt;Süd&gt;\t</Content></CharacterStyleRange>\
+        <CharacterStyleRange AppliedCharacterStyle=\"CharacterStyle/Index Reference\"><Content>C3-C4</Content></CharacterStyleRange><Br/></ParagraphStyleRange>"));
+}
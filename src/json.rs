@@ -0,0 +1,357 @@
+//! Export of the index as JSON and GeoJSON, i.e. for interactive web maps
+//!
+//! Each entry is exported with its name, category, reference and the outline
+//! of the grid cells it's listed in, in page or world coordinates.
+
+use std::collections::BTreeSet;
+use roads2csv::{ProcessedIndex, IndexName, GridPosition, FinalizedGridPositon};
+use gridconfig::{Grid, alphabet_value_to_number};
+use geometry::{PagePoint, contains_point};
+use units::{Millimeter, Float};
+
+/// Affine transformation from page to world coordinates (without rotation):
+/// `x = origin_x + x_from_left * scale_x`, `y = origin_y + y_from_top * scale_y`.
+/// For a map in UTM at 1:25000, the scale is 25 meter per millimeter and
+/// `scale_y` is -25, since the world coordinates grow to the north.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct WorldTransform {
+    /// World coordinates of the top left of the grid
+    pub origin_x: f64,
+    pub origin_y: f64,
+    /// World units per millimeter on the page
+    pub scale_x: f64,
+    pub scale_y: f64,
+}
+
+/// Coordinate system of the exported cell outlines
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Coordinates {
+    /// Millimeter on the page, from the top left of the grid
+    Page,
+    /// World coordinates of the map
+    World(WorldTransform),
+}
+
+/// Exports the index with the geometry of the grid that the index was created from
+#[derive(Debug, Copy, Clone)]
+pub struct JsonExport<'a> {
+    pub grid: &'a Grid,
+    pub coordinates: Coordinates,
+}
+
+/// One entry of an index with its cells
+struct JsonEntry<'a> {
+    name: &'a IndexName,
+    reference: String,
+    cells: Vec<GridPosition>,
+    /// Whether the entry is a processed road (or unprocessed, needs review)
+    processed: bool,
+    /// Target of a cross reference
+    see: Option<&'a IndexName>,
+}
+
+impl<'a> JsonExport<'a> {
+    /// Exports the outlines of the cells in page coordinates
+    pub fn new(grid: &'a Grid) -> Self {
+        Self { grid, coordinates: Coordinates::Page }
+    }
+
+    /// Exports the indexes as JSON:
+    ///
+    /// ```no_run,ignore
+    /// {"indexes":[{"category":"Streets","title":"Streets","entries":[
+    ///   {"name":"Castle Road","locality":null,"symbol":null,"label":"Castle Road",
+    ///    "reference":"B2-C2","cells":["B2","C2"],"processed":true,"see":null,
+    ///    "outline":[[[[50,50],[150,50],[150,100],[50,100],[50,50]]]]}, ...
+    /// ]}]}
+    /// ```
+    ///
+    /// The `outline` is the union of the cells of the entry, as the coordinates of a
+    /// GeoJSON `MultiPolygon`. Cross references have no cells, but a `see` entry.
+    /// The entries are ordered: processed roads, unprocessed roads, cross references.
+    pub fn json(&self, indexes: &[ProcessedIndex]) -> String {
+        let indexes = indexes.iter().map(|index| {
+            let entries = json_entries(index).iter().map(|entry| {
+                format!("{{\"name\":{},\"locality\":{},\"symbol\":{},\"label\":{},\"reference\":{},\"cells\":[{}],\"processed\":{},\"see\":{},\"outline\":{}}}",
+                    json_string(&entry.name.street_name.0),
+                    json_option(&entry.name.locality),
+                    json_option(&entry.name.symbol),
+                    json_string(&entry.name.to_string()),
+                    json_string(&entry.reference),
+                    entry.cells.iter().map(|cell| json_string(&cell.to_string())).collect::<Vec<String>>().join(","),
+                    entry.processed,
+                    entry.see.map(|see| json_string(&see.to_string())).unwrap_or_else(|| String::from("null")),
+                    self.outline_json(&entry.cells))
+            }).collect::<Vec<String>>();
+
+            format!("{{\"category\":{},\"title\":{},\"entries\":[{}]}}",
+                json_string(&index.category.to_string()),
+                json_string(&index.settings.title),
+                entries.join(","))
+        }).collect::<Vec<String>>();
+
+        format!("{{\"indexes\":[{}]}}", indexes.join(","))
+    }
+
+    /// Exports the entries of all indexes as a GeoJSON `FeatureCollection`. Each
+    /// feature is the union of the cells of one entry (as a `MultiPolygon`), with
+    /// the same properties as in `json`. Cross references are not exported.
+    pub fn geojson(&self, indexes: &[ProcessedIndex]) -> String {
+        let mut features = Vec::new();
+
+        for index in indexes {
+            for entry in json_entries(index).iter().filter(|entry| entry.see.is_none()) {
+                features.push(format!("{{\"type\":\"Feature\",\"properties\":{{\"name\":{},\"locality\":{},\"symbol\":{},\"label\":{},\"category\":{},\"reference\":{},\"cells\":[{}],\"processed\":{}}},\"geometry\":{{\"type\":\"MultiPolygon\",\"coordinates\":{}}}}}",
+                    json_string(&entry.name.street_name.0),
+                    json_option(&entry.name.locality),
+                    json_option(&entry.name.symbol),
+                    json_string(&entry.name.to_string()),
+                    json_string(&index.category.to_string()),
+                    json_string(&entry.reference),
+                    entry.cells.iter().map(|cell| json_string(&cell.to_string())).collect::<Vec<String>>().join(","),
+                    entry.processed,
+                    self.outline_json(&entry.cells)));
+            }
+        }
+
+        format!("{{\"type\":\"FeatureCollection\",\"features\":[{}]}}", features.join(","))
+    }
+
+    /// Returns the union of the cells as the coordinates of a GeoJSON `MultiPolygon`
+    fn outline_json(&self, cells: &[GridPosition]) -> String {
+        let cells = cells.iter().filter_map(|cell| {
+            let column = alphabet_value_to_number(&cell.column)?;
+            let row = cell.row.checked_sub(1)?;
+            if column < self.grid.column_count() && row < self.grid.row_count() { Some((column, row)) } else { None }
+        }).collect::<BTreeSet<(usize, usize)>>();
+
+        let polygons = cell_outline(&cells).into_iter().map(|rings| {
+            let rings = rings.into_iter().enumerate().map(|(ring_index, ring)| {
+                let mut points = ring.iter().map(|(column, row)| self.transform(*column, *row)).collect::<Vec<(f64, f64)>>();
+                // GeoJSON: the exterior ring is counterclockwise, holes are clockwise
+                if (signed_area(&points) > 0.0) != (ring_index == 0) {
+                    points.reverse();
+                }
+                points.push(points[0]);
+                let points = points.iter().map(|(x, y)| format!("[{},{}]", x, y)).collect::<Vec<String>>();
+                format!("[{}]", points.join(","))
+            }).collect::<Vec<String>>();
+            format!("[{}]", rings.join(","))
+        }).collect::<Vec<String>>();
+
+        format!("[{}]", polygons.join(","))
+    }
+
+    /// Returns the coordinates of the grid line crossing (column line, row line)
+    fn transform(&self, column: usize, row: usize) -> (f64, f64) {
+        let (x, y) = (self.grid.column_lines()[column], self.grid.row_lines()[row]);
+        match self.coordinates {
+            Coordinates::Page => (to_f64(x), to_f64(y)),
+            Coordinates::World(t) => (t.origin_x + to_f64(x) * t.scale_x, t.origin_y + to_f64(y) * t.scale_y),
+        }
+    }
+}
+
+/// Collects the entries of an index with their cells
+fn json_entries(index: &ProcessedIndex) -> Vec<JsonEntry<'_>> {
+    let mut entries = Vec::new();
+
+    for road in &index.processed.processed {
+        let cells = match &road.position {
+            FinalizedGridPositon::SingleRect(a) => vec![a.clone()],
+            FinalizedGridPositon::TwoRect(a, b) => vec![a.clone(), b.clone()],
+        };
+        entries.push(JsonEntry { name: &road.name, reference: road.reference(), cells, processed: true, see: None });
+    }
+
+    for road in &index.unprocessed.unprocessed {
+        entries.push(JsonEntry { name: &road.name, reference: road.reference(), cells: road.positions.clone(), processed: false, see: None });
+    }
+
+    for reference in &index.processed.cross_references {
+        entries.push(JsonEntry { name: &reference.name, reference: reference.reference(), cells: Vec::new(), processed: true, see: Some(&reference.target) });
+    }
+
+    entries
+}
+
+/// Traces the outline of a set of (column, row) cells. Returns the polygons of the
+/// outline, each as a list of rings (the exterior ring first, then the holes),
+/// with the corners as (column line, row line) indices.
+fn cell_outline(cells: &BTreeSet<(usize, usize)>) -> Vec<Vec<Vec<(usize, usize)>>> {
+
+    // The edges of all cells, going around each cell in the same direction.
+    // Edges between two cells cancel each other out, the rest is the outline.
+    let mut edges = BTreeSet::new();
+    for &(column, row) in cells {
+        let corners = [(column, row), (column + 1, row), (column + 1, row + 1), (column, row + 1)];
+        for i in 0..4 {
+            let edge = (corners[i], corners[(i + 1) % 4]);
+            if !edges.remove(&(edge.1, edge.0)) {
+                edges.insert(edge);
+            }
+        }
+    }
+
+    // Where cells only touch at a corner, two edges leave the corner. Taking the
+    // leftmost turn keeps following the same cell, so that the outline is split
+    // into separate rings instead of one ring that touches itself.
+    let turn = |from: (usize, usize), corner: (usize, usize), to: (usize, usize)| {
+        let incoming = (corner.0 as i64 - from.0 as i64, corner.1 as i64 - from.1 as i64);
+        let outgoing = (to.0 as i64 - corner.0 as i64, to.1 as i64 - corner.1 as i64);
+        match incoming.0 * outgoing.1 - incoming.1 * outgoing.0 {
+            cross if cross > 0 => 0, // left
+            0 => 1, // straight on
+            _ => 2, // right
+        }
+    };
+
+    let mut rings = Vec::new();
+    while let Some(&first) = edges.iter().next() {
+        edges.remove(&first);
+        let (start, mut next) = first;
+        let mut previous = start;
+        let mut ring = vec![start];
+        loop {
+            let mut outgoing = edges.range((next, (0, 0))..).take_while(|edge| edge.0 == next).cloned().collect::<Vec<_>>();
+            if next == start {
+                outgoing.push(first);
+            }
+            let edge = outgoing.into_iter().min_by_key(|edge| turn(previous, next, edge.1)).expect("outline of cells is always closed");
+            if edge == first {
+                break;
+            }
+            ring.push(next);
+            edges.remove(&edge);
+            previous = next;
+            next = edge.1;
+        }
+        rings.push(remove_collinear(ring));
+    }
+
+    // Rings in the direction of the cells are exteriors, the other ones are holes
+    let as_points = |ring: &[(usize, usize)]| ring.iter().map(|(c, r)| (*c as f64, *r as f64)).collect::<Vec<(f64, f64)>>();
+    let (exteriors, holes): (Vec<_>, Vec<_>) = rings.into_iter().partition(|ring| signed_area(&as_points(ring)) > 0.0);
+
+    let mut polygons = exteriors.into_iter().map(|exterior| vec![exterior]).collect::<Vec<Vec<Vec<(usize, usize)>>>>();
+    for hole in holes {
+        let (column, row) = hole[0];
+        let point = PagePoint::new(Millimeter(column as Float), Millimeter(row as Float));
+        let outline = |polygon: &Vec<Vec<(usize, usize)>>| polygon[0].iter().map(|(c, r)| PagePoint::new(Millimeter(*c as Float), Millimeter(*r as Float))).collect::<Vec<PagePoint>>();
+        if let Some(polygon) = polygons.iter_mut().find(|polygon| contains_point(&outline(polygon), point)) {
+            polygon.push(hole);
+        }
+    }
+
+    polygons
+}
+
+/// Removes the corners of a ring that lie on a straight line between their neighbours
+fn remove_collinear(ring: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    let len = ring.len();
+    (0..len).filter(|&i| {
+        let (previous, current, next) = (ring[(i + len - 1) % len], ring[i], ring[(i + 1) % len]);
+        let same_column = previous.0 == current.0 && current.0 == next.0;
+        let same_row = previous.1 == current.1 && current.1 == next.1;
+        !(same_column || same_row)
+    }).map(|i| ring[i]).collect()
+}
+
+/// Shoelace formula, positive for counterclockwise rings (with the y axis pointing up)
+fn signed_area(points: &[(f64, f64)]) -> f64 {
+    (0..points.len()).map(|i| {
+        let (a, b) = (points[i], points[(i + 1) % points.len()]);
+        a.0 * b.1 - b.0 * a.1
+    }).sum::<f64>() / 2.0
+}
+
+/// Converts via the decimal representation, so that `f32` coordinates
+/// such as `0.1` are not exported as `0.10000000149011612`
+fn to_f64(value: Millimeter) -> f64 {
+    value.0.to_string().parse().unwrap_or(0.0)
+}
+
+/// Formats a string as a JSON string literal
+fn json_string(text: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Formats an optional string as a JSON string or `null`
+fn json_option(text: &Option<String>) -> String {
+    text.as_ref().map(|text| json_string(text)).unwrap_or_else(|| String::from("null"))
+}
+
+#[test]
+fn test_json_export() {
+    use roads2csv::CategorizedRoads;
+    use gridconfig::{Bbox, GridConfig, AssignmentStrategy, StreetNameRect};
+
+    let mut grid = Grid::new(Bbox::new(Millimeter(150.0), Millimeter(150.0)), GridConfig {
+        assignment: AssignmentStrategy::FullCoverage,
+        .. Default::default()
+    });
+
+    // A1, B1, C1 and A2, C2, A3, B3, C3 (a ring around B2)
+    grid.insert_street(StreetNameRect::new("Long \"Road\"", Millimeter(10.0), Millimeter(10.0), Millimeter(130.0), Millimeter(5.0)));
+    grid.insert_street(StreetNameRect::new("Ring Road", Millimeter(10.0), Millimeter(60.0), Millimeter(30.0), Millimeter(80.0)));
+    grid.insert_street(StreetNameRect::new("Ring Road", Millimeter(110.0), Millimeter(60.0), Millimeter(30.0), Millimeter(80.0)));
+    grid.insert_street(StreetNameRect::new("Ring Road", Millimeter(10.0), Millimeter(110.0), Millimeter(130.0), Millimeter(5.0)));
+    grid.insert_street(StreetNameRect::new("Ring Road", Millimeter(10.0), Millimeter(10.0), Millimeter(130.0), Millimeter(5.0)));
+    grid.insert_street(StreetNameRect {
+        alternate_names: vec![String::from("Ffordd y Castell")],
+        .. StreetNameRect::new("Castle Road", Millimeter(60.0), Millimeter(60.0), Millimeter(20.0), Millimeter(5.0))
+    });
+
+    let indexes = CategorizedRoads::from_streets(&grid.street_names()).process();
+
+    let json = JsonExport::new(&grid).json(&indexes);
+    assert!(json.starts_with("{\"indexes\":[{\"category\":\"Streets\",\"title\":\"Streets\",\"entries\":[\
+        {\"name\":\"Castle Road\",\"locality\":null,\"symbol\":null,\"label\":\"Castle Road\",\"reference\":\"B2\",\"cells\":[\"B2\"],\
+        \"processed\":true,\"see\":null,\"outline\":[[[[50,50],[100,50],[100,100],[50,100],[50,50]]]]},\
+        {\"name\":\"Long \\\"Road\\\"\",\"locality\":null,\"symbol\":null,\"label\":\"Long \\\"Road\\\"\",\"reference\":\"A1, B1, C1\",\"cells\":[\"A1\",\"B1\",\"C1\"],\
+        \"processed\":false,\"see\":null,\"outline\":[[[[0,0],[150,0],[150,50],[0,50],[0,0]]]]},"));
+    assert!(json.ends_with("{\"name\":\"Ffordd y Castell\",\"locality\":null,\"symbol\":null,\"label\":\"Ffordd y Castell\",\"reference\":\"\u{2192} see Castle Road\",\
+        \"cells\":[],\"processed\":true,\"see\":\"Castle Road\",\"outline\":[]}]}]}"));
+
+    // The ring road is one polygon with a hole, in world coordinates
+    let export = JsonExport {
+        coordinates: Coordinates::World(WorldTransform { origin_x: 1000.0, origin_y: 5000.0, scale_x: 10.0, scale_y: -10.0 }),
+        .. JsonExport::new(&grid)
+    };
+    let geojson = export.geojson(&indexes);
+    assert!(geojson.starts_with("{\"type\":\"FeatureCollection\",\"features\":[{\"type\":\"Feature\",\"properties\":{\"name\":\"Castle Road\""));
+    assert!(geojson.contains("\"geometry\":{\"type\":\"MultiPolygon\",\"coordinates\":[[\
+        [[1000,3500],[2500,3500],[2500,5000],[1000,5000],[1000,3500]],\
+        [[2000,4500],[2000,4000],[1500,4000],[1500,4500],[2000,4500]]]]}}"));
+    assert!(!geojson.contains("Ffordd y Castell"));
+}
+
+#[test]
+fn test_cell_outline() {
+    // B1 and A2 only touch at a corner: two polygons with one square ring each
+    let cells = [(1, 0), (0, 1)].iter().cloned().collect::<BTreeSet<_>>();
+    assert_eq!(cell_outline(&cells), vec![
+        vec![vec![(0, 1), (1, 1), (1, 2), (0, 2)]],
+        vec![vec![(1, 0), (2, 0), (2, 1), (1, 1)]],
+    ]);
+
+    // A ring of cells around B2: the exterior and the hole
+    let cells = (0..3).flat_map(|column| (0..3).map(move |row| (column, row))).filter(|cell| *cell != (1, 1)).collect::<BTreeSet<_>>();
+    assert_eq!(cell_outline(&cells), vec![
+        vec![vec![(0, 0), (3, 0), (3, 3), (0, 3)], vec![(1, 1), (1, 2), (2, 2), (2, 1)]],
+    ]);
+}
+
@@ -0,0 +1,215 @@
+//! Export of the index as a LaTeX fragment, for atlases typeset with LaTeX
+//!
+//! The fragment is meant to be `\input` into a document that loads the
+//! `multicol` or `longtable` package. The entries are grouped by their
+//! initial letter, with dot leaders (`\dotfill`) between name and reference.
+
+use roads2csv::IndexEntry;
+use grouping::{LetterGrouping, base_letter};
+
+/// Environment that the entries are set in
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LatexEnvironment {
+    /// `multicols` environment with the number of columns (`multicol` package),
+    /// the letters are `\subsection*` headers
+    Multicols(usize),
+    /// Two-column `longtable` (`longtable` package) that breaks across pages,
+    /// the letters are bold rows spanning both columns
+    Longtable,
+}
+
+/// Settings of the LaTeX export
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatexExport {
+    /// `\section*` heading above the index, no heading if empty
+    pub title: String,
+    pub environment: LatexEnvironment,
+    /// Whether accented letters are written as LaTeX commands (`\"{a}`) so that
+    /// the fragment also works with pdfLaTeX without `inputenc`. If this is `false`,
+    /// the text is left as UTF-8, i.e. for XeLaTeX or LuaLaTeX.
+    pub ascii: bool,
+    /// Letter sections of the index, see `LetterGrouping`
+    pub grouping: LetterGrouping,
+}
+
+impl LatexExport {
+    /// No title, two columns, accented letters as LaTeX commands
+    pub fn new() -> Self {
+        Self {
+            title: String::new(),
+            environment: LatexEnvironment::Multicols(2),
+            ascii: true,
+            grouping: LetterGrouping::new(),
+        }
+    }
+
+    /// Exports the (sorted) entries as a LaTeX fragment, grouped by the initial letter
+    pub fn latex(&self, entries: &[IndexEntry]) -> String {
+        let escape = |text: &str| escape_latex(text, self.ascii);
+        let mut latex = String::new();
+
+        if !self.title.is_empty() {
+            latex.push_str(&format!("\\section*{{{}}}\n", escape(&self.title)));
+        }
+
+        match self.environment {
+            LatexEnvironment::Multicols(columns) => {
+                latex.push_str(&format!("\\begin{{multicols}}{{{}}}\n", columns));
+                for section in self.grouping.sections(entries) {
+                    latex.push_str(&format!("\\subsection*{{{}}}\n", escape(&section.letter)));
+                    for entry in section.entries {
+                        latex.push_str(&format!("\\noindent {}\\dotfill {}\\par\n", escape(&entry.name.to_string()), escape(&entry.reference)));
+                    }
+                }
+                latex.push_str("\\end{multicols}\n");
+            },
+            LatexEnvironment::Longtable => {
+                latex.push_str("\\begin{longtable}{@{}p{0.75\\linewidth}@{}r@{}}\n");
+                for section in self.grouping.sections(entries) {
+                    latex.push_str(&format!("\\multicolumn{{2}}{{@{{}}l}}{{\\textbf{{{}}}}} \\\\*\n", escape(&section.letter)));
+                    for entry in section.entries {
+                        latex.push_str(&format!("{}\\dotfill & {} \\\\\n", escape(&entry.name.to_string()), escape(&entry.reference)));
+                    }
+                }
+                latex.push_str("\\end{longtable}\n");
+            },
+        }
+
+        latex
+    }
+}
+
+impl Default for LatexExport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Escapes the characters that have a special meaning in LaTeX. If `ascii` is
+/// set, accented letters and some symbols are replaced by LaTeX commands;
+/// other characters are left as they are.
+fn escape_latex(text: &str, ascii: bool) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            },
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '\u{a0}' => escaped.push('~'),
+            // pdfLaTeX prints < and > as ¡ and ¿ in the default OT1 encoding
+            '<' if ascii => escaped.push_str("\\textless{}"),
+            '>' if ascii => escaped.push_str("\\textgreater{}"),
+            c if ascii && !c.is_ascii() => match latex_command(c) {
+                Some(command) => escaped.push_str(&command),
+                None => escaped.push(c),
+            },
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// LaTeX command for an accented letter (`ä` → `\"{a}`) or a symbol
+fn latex_command(c: char) -> Option<String> {
+    let symbol = match c {
+        'ß' => "\\ss{}",
+        'æ' => "\\ae{}",
+        'Æ' => "\\AE{}",
+        'ø' => "\\o{}",
+        'Ø' => "\\O{}",
+        'œ' => "\\oe{}",
+        'Œ' => "\\OE{}",
+        'ł' => "\\l{}",
+        'Ł' => "\\L{}",
+        'ı' => "\\i{}",
+        'å' => "\\aa{}",
+        'Å' => "\\AA{}",
+        '\u{2013}' => "--",
+        '\u{2014}' => "---",
+        '\u{2018}' => "`",
+        '\u{2019}' => "'",
+        '\u{201c}' => "``",
+        '\u{201d}' => "''",
+        '\u{2192}' => "$\\rightarrow$",
+        '\u{27e8}' => "$\\langle$",
+        '\u{27e9}' => "$\\rangle$",
+        '\u{b7}' => "\\textperiodcentered{}",
+        _ => "",
+    };
+    if !symbol.is_empty() {
+        return Some(symbol.to_string());
+    }
+
+    let accent = match c {
+        'à' | 'è' | 'ì' | 'ò' | 'ù' | 'À' | 'È' | 'Ì' | 'Ò' | 'Ù' => "`",
+        'á' | 'é' | 'í' | 'ó' | 'ú' | 'ý' | 'ć' | 'ń' | 'ś' | 'ź' | 'ĺ' | 'ŕ' |
+        'Á' | 'É' | 'Í' | 'Ó' | 'Ú' | 'Ý' | 'Ć' | 'Ń' | 'Ś' | 'Ź' | 'Ĺ' | 'Ŕ' => "'",
+        'â' | 'ê' | 'î' | 'ô' | 'û' | 'Â' | 'Ê' | 'Î' | 'Ô' | 'Û' => "^",
+        'ä' | 'ë' | 'ï' | 'ö' | 'ü' | 'ÿ' | 'Ä' | 'Ë' | 'Ï' | 'Ö' | 'Ü' | 'Ÿ' => "\"",
+        'ã' | 'ñ' | 'õ' | 'Ã' | 'Ñ' | 'Õ' => "~",
+        'ç' | 'ş' | 'ţ' | 'Ç' | 'Ş' | 'Ţ' => "c",
+        'č' | 'ď' | 'ě' | 'ľ' | 'ň' | 'ř' | 'š' | 'ť' | 'ž' |
+        'Č' | 'Ď' | 'Ě' | 'Ľ' | 'Ň' | 'Ř' | 'Š' | 'Ť' | 'Ž' => "v",
+        'ő' | 'ű' | 'Ő' | 'Ű' => "H",
+        'ą' | 'ę' | 'Ą' | 'Ę' => "k",
+        'ż' | 'Ż' => ".",
+        'ů' | 'Ů' => "r",
+        'ă' | 'ğ' | 'Ă' | 'Ğ' => "u",
+        'ā' | 'ē' | 'ī' | 'ō' | 'ū' | 'Ā' | 'Ē' | 'Ī' | 'Ō' | 'Ū' => "=",
+        _ => return None,
+    };
+
+    // Accents on the i are set on the dotless i
+    let base = match base_letter(c)? {
+        'i' => String::from("\\i"),
+        base => base.to_string(),
+    };
+    Some(format!("\\{}{{{}}}", accent, base))
+}
+
+#[test]
+fn test_latex_export() {
+    use roads2csv::{IndexName, StreetName};
+
+    let entries = [
+        IndexEntry::new("Abbey Road", "B2"),
+        IndexEntry::new("Apotheke & Ärztehaus", "C3-C4"),
+        IndexEntry::new("Bahnhofstraße", "\u{2192} see Main_Street #2"),
+        IndexEntry::new("Bay <East>", "B4"),
+        IndexEntry {
+            name: IndexName { symbol: Some(String::from("rail")), .. IndexName::from(StreetName(String::from("Central Station"))) },
+            reference: String::from("C4"),
+        },
+    ];
+
+    let latex = LatexExport { title: String::from("Straßen 100%"), .. LatexExport::new() }.latex(&entries);
+    assert_eq!(latex.lines().collect::<Vec<_>>(), vec![
+        "\\section*{Stra\\ss{}en 100\\%}",
+        "\\begin{multicols}{2}",
+        "\\subsection*{A}",
+        "\\noindent Abbey Road\\dotfill B2\\par",
+        "\\noindent Apotheke \\& \\\"{A}rztehaus\\dotfill C3-C4\\par",
+        "\\subsection*{B}",
+        "\\noindent Bahnhofstra\\ss{}e\\dotfill $\\rightarrow$ see Main\\_Street \\#2\\par",
+        "\\noindent Bay \\textless{}East\\textgreater{}\\dotfill B4\\par",
+        "\\subsection*{C}",
+        "\\noindent Central Station $\\langle$rail$\\rangle$\\dotfill C4\\par",
+        "\\end{multicols}",
+    ]);
+
+    let latex = LatexExport { environment: LatexEnvironment::Longtable, ascii: false, .. LatexExport::new() }.latex(&entries[..2]);
+    assert_eq!(latex.lines().collect::<Vec<_>>(), vec![
+        "\\begin{longtable}{@{}p{0.75\\linewidth}@{}r@{}}",
+        "\\multicolumn{2}{@{}l}{\\textbf{A}} \\\\*",
+        "Abbey Road\\dotfill & B2 \\\\",
+        "Apotheke \\& Ärztehaus\\dotfill & C3-C4 \\\\",
+        "\\end{longtable}",
+    ]);
+
+    assert_eq!(escape_latex("Łódź ~ Brăila", true), "\\L{}\\'{o}d\\'{z} \\textasciitilde{} Br\\u{a}ila");
+    assert_eq!(escape_latex("Bay <East>", false), "Bay <East>");
+}
@@ -0,0 +1,405 @@
+//! Typesetting of the finished index into pages and columns
+//!
+//! The layout only decides where each piece of text goes on which page.
+//! Texts are measured by a `TextMeasure` (ideally from the metrics of the
+//! font that the index is printed in), the result can be rendered to SVG
+//! via `IndexLayout::to_svg`.
+
+use std::fmt::Write;
+use roads2csv::IndexEntry;
+use units::{Millimeter, Point, Float};
+use geometry::PageRect;
+use grouping::LetterGrouping;
+
+/// Measures the width of a text, i.e. from the advance widths of a font
+pub trait TextMeasure {
+    /// Returns the width of `text` at the given font size
+    fn text_width(&self, text: &str, font_size: Millimeter) -> Millimeter;
+}
+
+/// Estimates the width of a text from the average width of a character,
+/// as a fraction of the font size (about `0.5` for most text fonts).
+/// Good enough for drafts, use the metrics of the real font for the final layout.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AverageCharWidth(pub Float);
+
+impl TextMeasure for AverageCharWidth {
+    fn text_width(&self, text: &str, font_size: Millimeter) -> Millimeter {
+        Millimeter(text.chars().count() as Float * self.0 * font_size.0)
+    }
+}
+
+/// Page size, text frame and typography of the index pages
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexLayoutConfig {
+    pub page_width: Millimeter,
+    pub page_height: Millimeter,
+    /// Area of the page that the columns are laid out in (the page minus the margins)
+    pub frame: PageRect,
+    pub columns: usize,
+    /// Space between two columns
+    pub column_gap: Millimeter,
+    /// Font family for the SVG output, i.e. `"Helvetica, Arial, sans-serif"`
+    pub font_family: String,
+    pub font_size: Millimeter,
+    /// Distance from one line of an entry to the next
+    pub line_height: Millimeter,
+    pub header_font_size: Millimeter,
+    /// Height of a letter section header, including the space around it
+    pub header_height: Millimeter,
+    /// Character of the leader between the name and the reference, i.e. `'.'`
+    pub leader: char,
+    /// Indent of the following lines of a name that doesn't fit on one line
+    pub indent: Millimeter,
+    /// Minimum number of entries that follow a section header in the same column,
+    /// otherwise the header is moved to the next column
+    pub keep_with_header: usize,
+    /// Minimum number of entries of a section at the top of the next column,
+    /// if the section is broken over two columns (widow control)
+    pub min_widow_entries: usize,
+    /// Letter sections of the index, see `LetterGrouping`
+    pub grouping: LetterGrouping,
+}
+
+impl IndexLayoutConfig {
+    /// Layout for pages of the given size with 15 mm margins, 4 mm between
+    /// the columns, 8 pt entries, 11 pt headers and dot leaders. Headers are
+    /// kept with 2 entries and at least 2 entries of a broken section are
+    /// moved to the next column.
+    pub fn new<U: Into<Millimeter>>(page_width: U, page_height: U, columns: usize) -> Self {
+        let (page_width, page_height) = (page_width.into(), page_height.into());
+        let margin = 15.0;
+        let font_size = Millimeter::from(Point(8.0));
+        let header_font_size = Millimeter::from(Point(11.0));
+
+        Self {
+            page_width,
+            page_height,
+            frame: PageRect::new(Millimeter(margin), Millimeter(margin), Millimeter(page_width.0 - 2.0 * margin), Millimeter(page_height.0 - 2.0 * margin)),
+            columns,
+            column_gap: Millimeter(4.0),
+            font_family: String::from("Helvetica, Arial, sans-serif"),
+            font_size,
+            line_height: Millimeter(font_size.0 * 1.25),
+            header_font_size,
+            header_height: Millimeter(header_font_size.0 * 2.0),
+            leader: '.',
+            indent: font_size,
+            keep_with_header: 2,
+            min_widow_entries: 2,
+            grouping: LetterGrouping::new(),
+        }
+    }
+
+    /// Width of one column
+    pub fn column_width(&self) -> Millimeter {
+        let columns = self.columns.max(1) as Float;
+        Millimeter((self.frame.width.0 - self.column_gap.0 * (columns - 1.0)) / columns)
+    }
+}
+
+/// What part of the index a `LayoutText` is, i.e. for choosing the font
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TextStyle {
+    /// Letter section header (`"A"`)
+    Header,
+    /// Name of an entry (or one line of it, for names that don't fit on one line)
+    Name,
+    /// Leader characters between the name and the reference
+    Leader,
+    /// Reference of an entry (`"B2-B3"`)
+    Reference,
+}
+
+/// Where a `LayoutText` is positioned relative to its x position
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TextAnchor {
+    /// The text starts at the x position (left-aligned)
+    Start,
+    /// The text ends at the x position (right-aligned)
+    End,
+}
+
+/// One piece of text, positioned on the page
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutText {
+    pub text: String,
+    pub x_from_left: Millimeter,
+    /// Position of the baseline of the text
+    pub y_from_top: Millimeter,
+    pub anchor: TextAnchor,
+    pub style: TextStyle,
+}
+
+/// One page of the typeset index
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutPage {
+    pub texts: Vec<LayoutText>,
+}
+
+/// Index, typeset into columns and pages:
+///
+/// - Entries are grouped under letter section headers (`"A"`, `"B"`, ..., `"#"` for digits),
+///   see `IndexLayoutConfig::grouping`.
+/// - The name is left-aligned, the reference right-aligned, with leaders in between.
+///   Names that are too long are broken over several lines, the reference is
+///   on the last line.
+/// - An entry is never broken over two columns, headers are kept with the first
+///   entries of their section and broken sections carry at least
+///   `min_widow_entries` entries over to the next column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexLayout {
+    pub config: IndexLayoutConfig,
+    pub pages: Vec<LayoutPage>,
+}
+
+/// Entry with its name broken into lines, ready for placement
+struct EntryBlock<'a> {
+    name_lines: Vec<String>,
+    entry: &'a IndexEntry,
+}
+
+/// Current position while filling the columns
+struct Cursor {
+    pages: Vec<LayoutPage>,
+    column: usize,
+    y: Float,
+}
+
+impl IndexLayout {
+    /// Lays out the entries (usually `ProcessedRoadNames::entries`) in the order
+    /// they are given, so the entries should be sorted by name.
+    pub fn new<M: TextMeasure>(entries: &[IndexEntry], config: IndexLayoutConfig, measure: &M) -> Self {
+
+        let column_width = config.column_width().0;
+        let top = config.frame.y_from_top.0;
+        let bottom = top + config.frame.height.0;
+        let fits = |cursor: &Cursor, height: Float| cursor.y + height <= bottom + Float::EPSILON * bottom;
+
+        let mut cursor = Cursor { pages: vec![LayoutPage { texts: Vec::new() }], column: 0, y: top };
+        let next_column = |cursor: &mut Cursor| {
+            cursor.column += 1;
+            if cursor.column >= config.columns.max(1) {
+                cursor.column = 0;
+                cursor.pages.push(LayoutPage { texts: Vec::new() });
+            }
+            cursor.y = top;
+        };
+
+        for section in config.grouping.sections(entries) {
+
+            let blocks = section.entries.iter().map(|entry| {
+                let reference_width = measure.text_width(&entry.reference, config.font_size).0;
+                let leader_width = measure.text_width(&config.leader.to_string(), config.font_size).0;
+                let available = column_width - reference_width - 3.0 * leader_width;
+                EntryBlock {
+                    name_lines: wrap(&entry.name.to_string(), available, available - config.indent.0, config.font_size, measure),
+                    entry,
+                }
+            }).collect::<Vec<EntryBlock>>();
+            let heights = blocks.iter().map(|block| block.name_lines.len() as Float * config.line_height.0).collect::<Vec<Float>>();
+
+            // Keep the header with the first entries of the section
+            let keep_height = config.header_height.0 + heights.iter().take(config.keep_with_header).sum::<Float>();
+            if !fits(&cursor, keep_height) && cursor.y > top {
+                next_column(&mut cursor);
+            }
+
+            let x = column_x(&config, cursor.column);
+            cursor.pages.last_mut().unwrap().texts.push(LayoutText {
+                text: section.letter.clone(),
+                x_from_left: Millimeter(x),
+                y_from_top: Millimeter(cursor.y + (config.header_height.0 + config.header_font_size.0 * 0.7) / 2.0),
+                anchor: TextAnchor::Start,
+                style: TextStyle::Header,
+            });
+            cursor.y += config.header_height.0;
+
+            let mut placed = 0;
+            while placed < blocks.len() {
+
+                // How many of the remaining entries fit into this column?
+                let mut fitting = 0;
+                let mut y = cursor.y;
+                while placed + fitting < blocks.len() && y + heights[placed + fitting] <= bottom + Float::EPSILON * bottom {
+                    y += heights[placed + fitting];
+                    fitting += 1;
+                }
+
+                let remaining = blocks.len() - placed - fitting;
+                let mut take = fitting;
+                if remaining > 0 && remaining < config.min_widow_entries {
+                    // Carry more entries over to the next column, unless that
+                    // would separate the header from its first entries
+                    let carried = fitting.saturating_sub(config.min_widow_entries - remaining);
+                    if placed > 0 || carried >= config.keep_with_header.min(blocks.len()) {
+                        take = carried;
+                    }
+                }
+                if take == 0 && cursor.y <= top {
+                    // The entry is taller than a whole column
+                    take = 1;
+                }
+
+                for (block, height) in blocks[placed..placed + take].iter().zip(&heights[placed..placed + take]) {
+                    let x = column_x(&config, cursor.column);
+                    let texts = entry_texts(block, x, cursor.y, &config, measure);
+                    cursor.pages.last_mut().unwrap().texts.extend(texts);
+                    cursor.y += height;
+                }
+
+                placed += take;
+                if placed < blocks.len() {
+                    next_column(&mut cursor);
+                }
+            }
+        }
+
+        Self { config, pages: cursor.pages }
+    }
+
+    /// Renders each page as an SVG document, in millimeter
+    pub fn to_svg(&self) -> Vec<String> {
+        let config = &self.config;
+        self.pages.iter().map(|page| {
+            let mut svg = String::new();
+            let _ = writeln!(svg, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}mm\" height=\"{h}mm\" viewBox=\"0 0 {w} {h}\">",
+                w = svg_number(config.page_width.0), h = svg_number(config.page_height.0));
+            let _ = writeln!(svg, "<g font-family=\"{}\" font-size=\"{}\">", escape_xml(&config.font_family), svg_number(config.font_size.0));
+            for text in &page.texts {
+                let mut attributes = String::new();
+                if text.anchor == TextAnchor::End {
+                    attributes.push_str(" text-anchor=\"end\"");
+                }
+                if text.style == TextStyle::Header {
+                    let _ = write!(attributes, " font-size=\"{}\" font-weight=\"bold\"", svg_number(config.header_font_size.0));
+                }
+                let _ = writeln!(svg, "<text x=\"{}\" y=\"{}\"{}>{}</text>",
+                    svg_number(text.x_from_left.0), svg_number(text.y_from_top.0), attributes, escape_xml(&text.text));
+            }
+            svg.push_str("</g>\n</svg>\n");
+            svg
+        }).collect()
+    }
+}
+
+/// X position of the left edge of a column
+fn column_x(config: &IndexLayoutConfig, column: usize) -> Float {
+    config.frame.x_from_left.0 + column as Float * (config.column_width().0 + config.column_gap.0)
+}
+
+/// Breaks a name into lines at the spaces, so that the first line is at most
+/// `first_width` and the following lines at most `width` wide. Words that are
+/// wider than a line are not broken.
+fn wrap<M: TextMeasure>(text: &str, first_width: Float, width: Float, font_size: Millimeter, measure: &M) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+
+    for word in text.split(' ') {
+        let candidate = if current.is_empty() { word.to_string() } else { format!("{} {}", current, word) };
+        let max_width = if lines.is_empty() { first_width } else { width };
+        if current.is_empty() || measure.text_width(&candidate, font_size).0 <= max_width {
+            current = candidate;
+        } else {
+            lines.push(current);
+            current = word.to_string();
+        }
+    }
+
+    lines.push(current);
+    lines
+}
+
+/// Positions the name lines, the leader and the reference of an entry
+/// whose first line starts at `y` (top of the line)
+fn entry_texts<M: TextMeasure>(block: &EntryBlock, x: Float, y: Float, config: &IndexLayoutConfig, measure: &M) -> Vec<LayoutText> {
+    let mut texts = Vec::new();
+    let baseline = |line: usize| y + line as Float * config.line_height.0 + (config.line_height.0 + config.font_size.0 * 0.7) / 2.0;
+    let right = x + config.column_width().0;
+
+    for (line, name) in block.name_lines.iter().enumerate() {
+        texts.push(LayoutText {
+            text: name.clone(),
+            x_from_left: Millimeter(if line == 0 { x } else { x + config.indent.0 }),
+            y_from_top: Millimeter(baseline(line)),
+            anchor: TextAnchor::Start,
+            style: TextStyle::Name,
+        });
+    }
+
+    let last_line = block.name_lines.len() - 1;
+    let name_end = texts[last_line].x_from_left.0 + measure.text_width(&block.name_lines[last_line], config.font_size).0;
+    let reference_start = right - measure.text_width(&block.entry.reference, config.font_size).0;
+    let leader_width = measure.text_width(&config.leader.to_string(), config.font_size).0;
+
+    // Leave half a leader of space on both sides of the leaders
+    if leader_width > 0.0 {
+        let count = ((reference_start - name_end) / leader_width - 1.0).floor();
+        if count >= 1.0 {
+            texts.push(LayoutText {
+                text: config.leader.to_string().repeat(count as usize),
+                x_from_left: Millimeter(reference_start - leader_width / 2.0),
+                y_from_top: Millimeter(baseline(last_line)),
+                anchor: TextAnchor::End,
+                style: TextStyle::Leader,
+            });
+        }
+    }
+
+    texts.push(LayoutText {
+        text: block.entry.reference.clone(),
+        x_from_left: Millimeter(right),
+        y_from_top: Millimeter(baseline(last_line)),
+        anchor: TextAnchor::End,
+        style: TextStyle::Reference,
+    });
+
+    texts
+}
+
+/// Formats a coordinate for SVG with at most 2 decimals
+fn svg_number(value: Float) -> String {
+    let formatted = format!("{:.2}", value);
+    formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Escapes the characters that aren't allowed in XML text and attributes
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[test]
+fn test_index_layout() {
+    let entry = |name: &str| IndexEntry::new(name, "A1");
+
+    let mut entries = vec![entry("Abbey Road"), entry("Acacia Avenue"), entry("Albert & Victoria Street")];
+    entries.extend((1..=7).map(|i| entry(&format!("Baker Street {}", i))));
+    entries.push(entry("Cedar Lane"));
+
+    // 2 columns, 30 mm high: one header (6 mm) + 6 entries (4 mm each) per column
+    let config = IndexLayoutConfig {
+        frame: PageRect::new(Millimeter(15.0), Millimeter(15.0), Millimeter(70.0), Millimeter(30.0)),
+        font_size: Millimeter(3.0),
+        line_height: Millimeter(4.0),
+        header_font_size: Millimeter(4.0),
+        header_height: Millimeter(6.0),
+        .. IndexLayoutConfig::new(Millimeter(100.0), Millimeter(60.0), 2)
+    };
+    let layout = IndexLayout::new(&entries, config, &AverageCharWidth(0.5));
+
+    let texts = |page: &LayoutPage, style| page.texts.iter().filter(|t| t.style == style).map(|t| t.text.clone()).collect::<Vec<_>>();
+
+    // "B" doesn't fit with 2 entries below "A", so it starts the second column.
+    // Only 6 of the 7 "B" entries fit, but 1 entry alone is a widow, so 2 go to the next page.
+    assert_eq!(layout.pages.len(), 2);
+    assert_eq!(texts(&layout.pages[0], TextStyle::Header), vec!["A", "B"]);
+    // "Albert & Victoria Street" is broken over two lines
+    assert_eq!(texts(&layout.pages[0], TextStyle::Name).len(), 9);
+    assert_eq!(texts(&layout.pages[1], TextStyle::Header), vec!["C"]);
+    assert_eq!(texts(&layout.pages[1], TextStyle::Name), vec!["Baker Street 6", "Baker Street 7", "Cedar Lane"]);
+
+    let svg = &layout.to_svg()[0];
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100mm\" height=\"60mm\" viewBox=\"0 0 100 60\">"));
+    assert!(svg.contains(">Albert &amp; Victoria</text>"));
+    assert!(svg.contains("<text x=\"48\" y=\"24.05\" text-anchor=\"end\">A1</text>"));
+}
@@ -3,13 +3,15 @@
 //! [![LICENSE](https://img.shields.io/badge/license-MIT-blue.svg)](LICENSE)
 //! [![Build Status Linux / macOS](https://travis-ci.org/fschutt/street_index.svg?branch=master)](https://travis-ci.org/fschutt/street_index)
 //! [![Build status Windows](https://ci.appveyor.com/api/projects/status/0579ea95rbpliyhi?svg=true)](https://ci.appveyor.com/project/fschutt/street-index)
-//! [![Rust Compiler Version](https://img.shields.io/badge/rustc-1.26%20stable-blue.svg)]()
+//! [![Rust Compiler Version](https://img.shields.io/badge/rustc-1.75%20stable-blue.svg)]()
 //!
 //! This library contains utility functions for generating a street index.
 //! How it works is fairly simple: You give it a grid (right now limited
 //! to a rectangular grid) on a page, and add `StreetNameRect`s. Each
 //! `StreetNameRect` contains the String for the street / road name as well
 //! as the extents of the laid out String on the map.
+//! All coordinates are in millimeter, but `Point`s, `Inch`es and `Pixel`s
+//! can be converted to millimeter via `From` / `Into`.
 //!
 //! The `Grid` takes care of assigning a grid position to your street name
 //! such as `"Canterbury Road => A2"`. Since usually maps have the problem
@@ -39,6 +41,8 @@
 //! that your graphic designer can paste the street index into InDesign /
 //! Illustrator for the final map layout. Both `UnprocessedRoads` and
 //! `ProcessedRoads` have a simple `.to_csv` function for easy export.
+//! The finished index can also be typeset into columns and pages with letter
+//! headers and dot leaders (`IndexLayout`) and rendered as SVG.
 //!
 //! ## Example
 //!
@@ -48,8 +52,8 @@
 //! use street_index::prelude::*;
 //!
 //! fn main() {
-//! 	// Create a grid, with the page extensions being 200 x 200 millimeter
-//! 	// Each cell is 20x20 millimeter large (usually 50x50 is recommended, though)
+//!     // Create a grid, with the page extensions being 200 x 200 millimeter
+//!     // Each cell is 20x20 millimeter large (usually 50x50 is recommended, though)
 //!     let mut grid = Grid::new(
 //!             Bbox {
 //!                 width: Millimeter(200.0),
@@ -58,6 +62,7 @@
 //!             GridConfig {
 //!                 cell_width: Millimeter(20.0),
 //!                 cell_height: Millimeter(20.0),
+//!                 .. Default::default()
 //!             });
 //!
 //!     // You will have to calculate the street name boundaries yourself, i.e.
@@ -71,6 +76,9 @@
 //!         width: Millimeter(50.0),
 //!         y_from_top: Millimeter(30.0),
 //!         height: Millimeter(8.0),
+//!         // Category::Street by default, see `CategorizedRoads` for
+//!         // publishing separate indexes for parks, places, etc.
+//!         .. Default::default()
 //!     });
 //!
 //!     // We deduplicate the roads, i.e.:
@@ -94,32 +102,129 @@
 //!     // In this case, "Canterbury Road" spans from B1-B2, so we get a
 //!     // `ProcessedRoad` back, delimited by a TAB character.
 //!     //
-//!  	// You can then write this to a CSV file if you want.
+//!     // You can then write this to a CSV file if you want.
 //!     println!("processed:\r\n{}", processed.to_csv("\t"));
 //!     println!("unprocessed:\r\n{}", unprocessed.to_csv("\t"));
 //! }
 //! ```
 //!
+//! ## Features
+//!
+//! - `f64`: Use `f64` instead of `f32` for all coordinates (see `units::Float`),
+//!   for large-format maps where `f32` isn't precise enough at the cell boundaries.
+//! - `pdf`: Write the typeset index as a PDF with an embedded font
+//!   (`IndexLayout::to_pdf`), so small jobs don't need a DTP application.
+//! - `xlsx`: Write the index as an XLSX workbook for the editorial review of the
+//!   unprocessed roads and read the decisions back (`ProcessedIndex::to_xlsx`).
+//!
 //! ## License
 //!
 //! This library is licensed under the MIT license.
 #![cfg_attr(feature = "nightly", feature(test))]
 #[cfg(feature = "nightly")]
 extern crate test;
+#[cfg(feature = "pdf")]
+extern crate printpdf;
+#[cfg(feature = "pdf")]
+extern crate ttf_parser;
+#[cfg(feature = "xlsx")]
+extern crate rust_xlsxwriter;
+#[cfg(feature = "xlsx")]
+extern crate calamine;
 
 /// Module for configuring the grid and assigning cell positions to road names
 pub mod gridconfig;
 /// Module for deduplicating road names and exporting / processing them to CSV
 pub mod roads2csv;
+/// Module for the units of measurement (millimeter, points, inch, pixel)
+pub mod units;
+/// Module for points, rectangles and polygons on the page
+pub mod geometry;
+/// Module for expanding abbreviated street names (`"Canterbury Rd"`) to the full name
+pub mod abbreviations;
+/// Module for grouping the index into letter sections with language-specific rules
+pub mod grouping;
+/// Module for typesetting the finished index into columns and pages (SVG output)
+pub mod layout;
+/// Module for exporting the index to Adobe InDesign (Tagged Text, IDML)
+pub mod indesign;
+/// Module for exporting the index as HTML and Markdown for web publication
+pub mod web;
+/// Module for exporting the index as JSON and GeoJSON with the outlines of the cells
+pub mod json;
+/// Module for exporting the index as a LaTeX fragment (`multicols` or `longtable`)
+pub mod latex;
+/// Module for writing the index with custom templates for the lines (`"{name}, {reference}"`)
+pub mod template;
+/// Module for writing the typeset index as a PDF with an embedded font
+#[cfg(feature = "pdf")]
+pub mod pdf;
+/// Module for the XLSX workbooks for reviewing the unprocessed roads
+#[cfg(feature = "xlsx")]
+pub mod spreadsheet;
 /// Quick re-exports for wildcard imports
 pub mod prelude {
 	pub use roads2csv::{
 	    InputStreetValue, DeduplicatedRoads, ProcessedRoad,
 	    ProcessedRoadNames, UnprocessedRoad, UnprocessedRoadNames,
-	    StreetName, GridPosition, FinalizedGridPositon,
+	    StreetName, IndexName, GridPosition, FinalizedGridPositon, Category,
+	    CategorizedRoads, ProcessedIndex, IndexSettings, SortOrder, LocalityQualifier,
+	    AlternateNames, CrossReference, InputAddress, HouseNumberSides, HouseNumberSide,
+	    HouseNumberRange, IndexEntry, ReviewDecision, ReviewResolution,
 	};
 
 	pub use gridconfig::{
-		Grid, GridConfig, Bbox, Millimeter, StreetNameRect,
+		Grid, GridConfig, PartialCellPolicy, MinimumOverlap, AssignmentStrategy,
+		Bbox, StreetNameRect, RoadLine, AreaFeature, AreaReference, PointFeature,
+		District, AddressPoint, merge_label_fragments,
+	};
+
+	pub use units::{
+		Millimeter, Point, Inch, Pixel,
+	};
+
+	pub use geometry::{
+		PagePoint, PageRect,
+	};
+
+	pub use abbreviations::{
+		AbbreviationExpander, AbbreviationRule,
+	};
+
+	pub use grouping::{
+		LetterGrouping, IndexSection,
+	};
+
+	pub use layout::{
+		TextMeasure, AverageCharWidth, IndexLayoutConfig, IndexLayout, LayoutPage,
+		LayoutText, TextStyle, TextAnchor,
+	};
+
+	pub use indesign::InDesignExport;
+
+	pub use web::{
+		HtmlExport, MarkdownExport,
+	};
+
+	pub use json::{
+		JsonExport, Coordinates, WorldTransform,
+	};
+
+	pub use latex::{
+		LatexExport, LatexEnvironment,
+	};
+
+	pub use template::{
+		Template, TemplatePart, TemplateField, TemplateError, TemplateExport,
+	};
+
+	#[cfg(feature = "pdf")]
+	pub use pdf::{
+		PdfFont, PdfError,
+	};
+
+	#[cfg(feature = "xlsx")]
+	pub use spreadsheet::{
+		SpreadsheetError, read_review,
 	};
 }
\ No newline at end of file
@@ -0,0 +1,172 @@
+//! PDF output of the typeset index (`pdf` feature)
+//!
+//! The pages of an `IndexLayout` are written with `printpdf`, with the font
+//! embedded into the PDF. Use the same `PdfFont` for measuring the texts of
+//! the layout, so that the leaders and the right-aligned references line up.
+
+use std::{fmt, error::Error, io::Cursor};
+use std::collections::BTreeMap;
+use printpdf::{self, PdfDocument, Mm};
+use ttf_parser::{Face, GlyphId};
+use layout::{IndexLayout, TextMeasure, TextAnchor, TextStyle};
+use units::{Millimeter, Point, Float};
+
+/// Error while writing the index as a PDF
+#[derive(Debug)]
+pub enum PdfError {
+    /// The font is not a valid TrueType / OpenType font
+    InvalidFont,
+    /// Error of the PDF writer
+    Pdf(printpdf::Error),
+}
+
+impl fmt::Display for PdfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PdfError::InvalidFont => write!(f, "invalid TrueType / OpenType font"),
+            PdfError::Pdf(e) => write!(f, "could not write PDF: {}", e),
+        }
+    }
+}
+
+impl Error for PdfError { }
+
+impl From<printpdf::Error> for PdfError {
+    fn from(e: printpdf::Error) -> Self {
+        PdfError::Pdf(e)
+    }
+}
+
+/// TrueType / OpenType font that is embedded into the PDF. Also measures
+/// the texts of the layout from the advance widths of its glyphs.
+#[derive(Debug, Clone)]
+pub struct PdfFont {
+    data: Vec<u8>,
+    units_per_em: Float,
+    /// Advance widths of the characters in the `cmap` of the font, in font units
+    advances: BTreeMap<char, u16>,
+    /// Advance width of the .notdef glyph, which is printed for characters that
+    /// aren't in the font
+    notdef_advance: u16,
+}
+
+impl PdfFont {
+    /// Loads a font from the contents of a `.ttf` / `.otf` file
+    pub fn new(data: Vec<u8>) -> Result<Self, PdfError> {
+        let (units_per_em, advances, notdef_advance) = {
+            let face = Face::parse(&data, 0).map_err(|_| PdfError::InvalidFont)?;
+
+            let mut advances = BTreeMap::new();
+            for subtable in face.tables().cmap.into_iter().flat_map(|cmap| cmap.subtables).filter(|subtable| subtable.is_unicode()) {
+                subtable.codepoints(|codepoint| {
+                    let advance = ::std::char::from_u32(codepoint)
+                        .and_then(|c| face.glyph_index(c).map(|glyph| (c, glyph)))
+                        .and_then(|(c, glyph)| face.glyph_hor_advance(glyph).map(|advance| (c, advance)));
+                    if let Some((c, advance)) = advance {
+                        advances.insert(c, advance);
+                    }
+                });
+            }
+
+            (face.units_per_em() as Float, advances, face.glyph_hor_advance(GlyphId(0)).unwrap_or(0))
+        };
+
+        Ok(Self { data, units_per_em, advances, notdef_advance })
+    }
+}
+
+impl TextMeasure for PdfFont {
+    fn text_width(&self, text: &str, font_size: Millimeter) -> Millimeter {
+        let units = text.chars()
+            .map(|c| *self.advances.get(&c).unwrap_or(&self.notdef_advance) as Float)
+            .sum::<Float>();
+
+        Millimeter(units / self.units_per_em * font_size.0)
+    }
+}
+
+impl IndexLayout {
+    /// Writes the pages as a PDF document, with the fonts embedded. The letter
+    /// section headers are printed in the `header_font` (i.e. the bold cut of
+    /// the font) or, if it's not set, in the `font`.
+    pub fn to_pdf(&self, title: &str, font: &PdfFont, header_font: Option<&PdfFont>) -> Result<Vec<u8>, PdfError> {
+        let config = &self.config;
+        let (width, height) = (Mm(to_f32(config.page_width.0)), Mm(to_f32(config.page_height.0)));
+        let (document, first_page, first_layer) = PdfDocument::new(title, width, height, "Index");
+
+        let text_font = document.add_external_font(Cursor::new(&font.data))?;
+        let header = match header_font {
+            Some(header_font) => (header_font, document.add_external_font(Cursor::new(&header_font.data))?),
+            None => (font, text_font.clone()),
+        };
+
+        for (page_index, page) in self.pages.iter().enumerate() {
+            let (page_ref, layer_ref) = if page_index == 0 {
+                (first_page, first_layer)
+            } else {
+                document.add_page(width, height, "Index")
+            };
+            let layer = document.get_page(page_ref).get_layer(layer_ref);
+
+            for text in &page.texts {
+                let (font, font_ref, font_size) = match text.style {
+                    TextStyle::Header => (header.0, &header.1, config.header_font_size),
+                    _ => (font, &text_font, config.font_size),
+                };
+                let x = match text.anchor {
+                    TextAnchor::Start => text.x_from_left.0,
+                    TextAnchor::End => text.x_from_left.0 - font.text_width(&text.text, font_size).0,
+                };
+                // PDF coordinates start at the bottom left of the page
+                let y = config.page_height.0 - text.y_from_top.0;
+                layer.use_text(text.text.clone(), to_f32(Point::from(font_size).0), Mm(to_f32(x)), Mm(to_f32(y)), font_ref);
+            }
+        }
+
+        Ok(document.save_to_bytes()?)
+    }
+}
+
+/// `printpdf` works with `f32`, but `Float` may be `f64`
+#[allow(clippy::unnecessary_cast)]
+fn to_f32(value: Float) -> f32 {
+    value as f32
+}
+
+#[test]
+fn test_invalid_font() {
+    match PdfFont::new(b"not a font".to_vec()) {
+        Err(PdfError::InvalidFont) => { },
+        other => panic!("expected PdfError::InvalidFont, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_pdf_pages() {
+    use roads2csv::IndexEntry;
+    use layout::IndexLayoutConfig;
+    use geometry::PageRect;
+
+    // Test font with a box of 0.6 em for each printable ASCII character
+    let font = PdfFont::new(include_bytes!("../tests/fonts/IndexTest.ttf").to_vec()).unwrap();
+    assert_eq!(font.text_width("Abbey Road", Millimeter(5.0)), Millimeter(30.0));
+    assert_eq!(font.text_width("Ä", Millimeter(5.0)), Millimeter(3.0));
+
+    // 2 columns of one header + 6 entries (of one line each), so 30 entries take 3 pages
+    let entries = (1..=30).map(|i| IndexEntry::new(&format!("Lane {}", i), "A1")).collect::<Vec<_>>();
+    let config = IndexLayoutConfig {
+        frame: PageRect::new(Millimeter(15.0), Millimeter(15.0), Millimeter(70.0), Millimeter(30.0)),
+        font_size: Millimeter(3.0),
+        line_height: Millimeter(4.0),
+        header_font_size: Millimeter(4.0),
+        header_height: Millimeter(6.0),
+        .. IndexLayoutConfig::new(Millimeter(100.0), Millimeter(60.0), 2)
+    };
+    let layout = IndexLayout::new(&entries, config, &font);
+    assert_eq!(layout.pages.len(), 3);
+
+    let pdf = layout.to_pdf("Index", &font, None).unwrap();
+    let pdf = String::from_utf8_lossy(&pdf);
+    assert!(pdf.starts_with("%PDF-"));
+    assert_eq!(pdf.matches("/Type/Page/").count(), 3);
+}
@@ -18,6 +18,196 @@ impl fmt::Display for StreetName {
 pub struct InputStreetValue {
     pub street_name: StreetName,
     pub position: GridPosition,
+    pub category: Category,
+    /// Symbol code of a point of interest, i.e. `"rail"` for a train station
+    pub symbol: Option<String>,
+    /// Municipality / district of the street, i.e. `"Altdorf"`
+    pub locality: Option<String>,
+    /// Other official names of the street, i.e. the name in the second language
+    /// of a bilingual region or the former name of a renamed street
+    pub alternate_names: Vec<StreetName>,
+}
+
+impl InputStreetValue {
+    /// Street of the category `Street` without symbol, locality or alternate names
+    pub fn new(street_name: &str, position: GridPosition) -> Self {
+        Self {
+            street_name: StreetName(street_name.to_string()),
+            position,
+            category: Category::Street,
+            symbol: None,
+            locality: None,
+            alternate_names: Vec::new(),
+        }
+    }
+
+    /// Returns the name under which the street is listed in the index
+    pub fn index_name(&self) -> IndexName {
+        IndexName {
+            street_name: self.street_name.clone(),
+            locality: self.locality.clone(),
+            symbol: self.symbol.clone(),
+        }
+    }
+}
+
+/// Address point of a street, see `Grid::insert_address`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InputAddress {
+    pub street_name: StreetName,
+    /// Municipality / district of the street, see `InputStreetValue::locality`
+    pub locality: Option<String>,
+    /// Numeric part of the house number, i.e. `12` for `"12a"`
+    pub house_number: u32,
+    pub position: GridPosition,
+}
+
+impl InputAddress {
+    /// Returns the name of the street that the address belongs to
+    pub fn index_name(&self) -> IndexName {
+        IndexName {
+            street_name: self.street_name.clone(),
+            locality: self.locality.clone(),
+            symbol: None,
+        }
+    }
+}
+
+/// Name of an entry as listed in the index: the street name plus the
+/// locality (if the same street name exists in several municipalities)
+/// and the symbol code for points of interest, i.e. `"Hauptstraße (Altdorf)"`
+/// or `"Central Station ⟨rail⟩"`.
+///
+/// Entries are deduplicated by the whole `IndexName`, so a station and a bus
+/// stop with the same name but different symbols are listed separately.
+#[derive(Debug, Clone, PartialEq, Ord, PartialOrd, Eq, Hash)]
+pub struct IndexName {
+    pub street_name: StreetName,
+    pub locality: Option<String>,
+    pub symbol: Option<String>,
+}
+
+impl From<StreetName> for IndexName {
+    fn from(street_name: StreetName) -> Self {
+        Self { street_name, locality: None, symbol: None }
+    }
+}
+
+impl fmt::Display for IndexName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.street_name)?;
+        if let Some(locality) = &self.locality {
+            write!(f, " ({})", locality)?;
+        }
+        if let Some(symbol) = &self.symbol {
+            write!(f, " \u{27E8}{}\u{27E9}", symbol)?;
+        }
+        Ok(())
+    }
+}
+
+/// When the locality of a street is shown in the index
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LocalityQualifier {
+    /// Every street with a locality is shown with it: `"Hauptstraße (Altdorf)"`
+    Always,
+    /// Only streets whose name exists in several localities are shown with the
+    /// locality, all other streets are shown without it
+    OnlyWhenDuplicated,
+}
+
+/// How the alternate names of a street (see `InputStreetValue::alternate_names`)
+/// are listed in the index
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AlternateNames {
+    /// Each alternate name gets a row pointing to the main name:
+    /// `"Ffordd y Castell → see Castle Road"`
+    CrossReference,
+    /// The street is listed under each of its names, with the same grid positions
+    Duplicate,
+    /// Alternate names are not listed
+    Ignore,
+}
+
+/// How the house numbers of a street are combined into ranges
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HouseNumberSides {
+    /// One range per cell for both sides of the street: `"1-47 B2"`
+    Combined,
+    /// Separate ranges for the odd and the even side of the street:
+    /// `"1-45 (odd) B2, 2-48 (even) B2"`
+    OddEven,
+}
+
+/// Side of the street that a `HouseNumberRange` belongs to
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HouseNumberSide {
+    Both,
+    Odd,
+    Even,
+}
+
+/// Range of the house numbers of a street within one grid cell, i.e. `"1-47 B2"`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HouseNumberRange {
+    /// Lowest house number in the cell
+    pub from: u32,
+    /// Highest house number in the cell
+    pub to: u32,
+    pub side: HouseNumberSide,
+    pub position: GridPosition,
+}
+
+impl fmt::Display for HouseNumberRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.from == self.to {
+            write!(f, "{}", self.from)?;
+        } else {
+            write!(f, "{}-{}", self.from, self.to)?;
+        }
+        match self.side {
+            HouseNumberSide::Both => { },
+            HouseNumberSide::Odd => write!(f, " (odd)")?,
+            HouseNumberSide::Even => write!(f, " (even)")?,
+        }
+        write!(f, " {}", self.position)
+    }
+}
+
+/// Category of an index entry. Maps usually publish a separate
+/// index for each category, see `CategorizedRoads`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Category {
+    /// Streets, roads, squares (the default)
+    Street,
+    /// Places / localities, i.e. villages or districts
+    Place,
+    /// Points of interest, i.e. hospitals, schools or stations
+    PointOfInterest,
+    /// Parks and other green areas
+    Park,
+    /// Any other category
+    Other(String),
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for Category {
+    fn default() -> Self {
+        Category::Street
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::Category::*;
+        match self {
+            Street => write!(f, "Streets"),
+            Place => write!(f, "Places"),
+            PointOfInterest => write!(f, "Points of interest"),
+            Park => write!(f, "Parks"),
+            Other(name) => write!(f, "{}", name),
+        }
+    }
 }
 
 /// Grid position such as "A9", "B4" or similar
@@ -33,10 +223,36 @@ impl fmt::Display for GridPosition {
     }
 }
 
+impl GridPosition {
+    /// Key for sorting positions in the order of the grid: `A1 < A2 < B1 < Z1 < AA1`
+    /// (the derived `Ord` compares the column as a string, so `AA1 < B1`)
+    fn grid_order(&self) -> (usize, &str, usize) {
+        (self.column.len(), &self.column, self.row)
+    }
+
+    /// Parses a position such as `"B4"` or `"AA12"` (uppercase column, row from 1)
+    pub fn parse(position: &str) -> Option<Self> {
+        let position = position.trim();
+        let letters = position.chars().take_while(|c| c.is_ascii_uppercase()).count();
+        let (column, row) = position.split_at(letters);
+        if column.is_empty() || row.is_empty() || !row.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        match row.parse() {
+            Ok(0) | Err(_) => None,
+            Ok(row) => Some(GridPosition { column: column.to_string(), row }),
+        }
+    }
+}
+
 /// Deduplicates road names, merging the roads by their name
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct DeduplicatedRoads {
-    pub roads: BTreeMap<StreetName, BTreeSet<GridPosition>>,
+    pub roads: BTreeMap<IndexName, BTreeSet<GridPosition>>,
+    /// Alternate names of the roads, by the name the road is listed under
+    pub alternate_names: BTreeMap<IndexName, BTreeSet<StreetName>>,
+    /// House numbers of the roads in each cell, see `add_house_numbers`
+    pub house_numbers: BTreeMap<IndexName, BTreeMap<GridPosition, BTreeSet<u32>>>,
 }
 
 impl DeduplicatedRoads {
@@ -57,17 +273,159 @@ impl DeduplicatedRoads {
     /// ```
     ///
     /// The output road name positions are ordered.
+    ///
+    /// The alternate names of all inputs with the same name are merged as well.
     pub fn from_streets(streets: &[InputStreetValue]) -> Self {
         let mut deduplicated_names = BTreeMap::new();
+        let mut alternate_names = BTreeMap::new();
 
         for input_street in streets {
             deduplicated_names
-            .entry(input_street.street_name.clone())
-            .or_insert_with(|| BTreeSet::new())
+            .entry(input_street.index_name())
+            .or_insert_with(BTreeSet::new)
             .insert(input_street.position.clone());
+
+            if !input_street.alternate_names.is_empty() {
+                alternate_names
+                .entry(input_street.index_name())
+                .or_insert_with(BTreeSet::new)
+                .extend(input_street.alternate_names.iter().cloned());
+            }
+        }
+
+        Self { roads: deduplicated_names, alternate_names, house_numbers: BTreeMap::new() }
+    }
+
+    /// Adds the house numbers of address points to the roads, so that the
+    /// processed roads are listed with house number ranges per cell
+    /// (`"Canterbury Road 1-47 B2, 48-120 B3"`).
+    ///
+    /// An address belongs to the road with the same name and locality at the
+    /// cell of the address. If there is none, it belongs to the road with the
+    /// same street name at that cell, if there is exactly one: the locality of a
+    /// label that straddles a district border is the district with the larger
+    /// part of the label, while the address may lie in the other district.
+    /// Addresses in cells that no road of the street is listed at are ignored.
+    pub fn add_house_numbers(&mut self, addresses: &[InputAddress]) {
+        for address in addresses {
+            let exact_name = address.index_name();
+            let at_position = self.roads.iter()
+                .filter(|(road, positions)| road.street_name == address.street_name && road.symbol.is_none() && positions.contains(&address.position))
+                .map(|(road, _)| road)
+                .collect::<Vec<&IndexName>>();
+            let name = match at_position.as_slice() {
+                _ if at_position.contains(&&exact_name) => exact_name,
+                [road] => (*road).clone(),
+                _ => continue,
+            };
+            self.house_numbers
+            .entry(name)
+            .or_default()
+            .entry(address.position.clone())
+            .or_default()
+            .insert(address.house_number);
+        }
+    }
+
+    /// Returns the house number ranges of the road, ordered by side and number.
+    /// Only the cells that the road is listed at have ranges, addresses in
+    /// other cells are ignored.
+    pub fn house_number_ranges(&self, name: &IndexName, sides: HouseNumberSides) -> Vec<HouseNumberRange> {
+        let mut ranges = Vec::new();
+        let road_positions = match self.roads.get(name) {
+            Some(positions) => positions,
+            None => return ranges,
+        };
+
+        for (position, numbers) in self.house_numbers.get(name).into_iter().flatten() {
+            if !road_positions.contains(position) {
+                continue;
+            }
+            let mut by_side = BTreeMap::new();
+            for number in numbers {
+                let side = match sides {
+                    HouseNumberSides::Combined => HouseNumberSide::Both,
+                    HouseNumberSides::OddEven if number % 2 == 1 => HouseNumberSide::Odd,
+                    HouseNumberSides::OddEven => HouseNumberSide::Even,
+                };
+                // numbers are sorted, so the first one is the lowest
+                by_side.entry(side).or_insert((*number, *number)).1 = *number;
+            }
+            ranges.extend(by_side.into_iter().map(|(side, (from, to))| HouseNumberRange { from, to, side, position: position.clone() }));
+        }
+
+        ranges.sort_by(|a, b| (a.side, a.from).cmp(&(b.side, b.from)).then_with(|| a.position.grid_order().cmp(&b.position.grid_order())));
+        ranges
+    }
+
+    /// Removes the locality from all entries whose name only exists once,
+    /// so that only duplicated street names are qualified by their locality:
+    ///
+    /// ```no_run,ignore
+    /// Hauptstraße (Altdorf) -> [B2]
+    /// Hauptstraße (Neudorf) -> [F7]
+    /// Kirchweg (Altdorf) -> [C3]
+    /// ```
+    ///
+    /// becomes:
+    ///
+    /// ```no_run,ignore
+    /// Hauptstraße (Altdorf) -> [B2]
+    /// Hauptstraße (Neudorf) -> [F7]
+    /// Kirchweg -> [C3]
+    /// ```
+    pub fn remove_unique_localities(&mut self) {
+        let mut name_count = BTreeMap::new();
+        for name in self.roads.keys() {
+            *name_count.entry((&name.street_name, &name.symbol)).or_insert(0) += 1;
+        }
+
+        let unique = self.roads.keys()
+            .filter(|name| name.locality.is_some() && name_count[&(&name.street_name, &name.symbol)] == 1)
+            .cloned()
+            .collect::<Vec<IndexName>>();
+
+        for name in unique {
+            let positions = self.roads.remove(&name).unwrap_or_default();
+            if let Some(alternate_names) = self.alternate_names.remove(&name) {
+                self.alternate_names.insert(IndexName { locality: None, .. name.clone() }, alternate_names);
+            }
+            if let Some(house_numbers) = self.house_numbers.remove(&name) {
+                self.house_numbers.insert(IndexName { locality: None, .. name.clone() }, house_numbers);
+            }
+            self.roads.insert(IndexName { locality: None, .. name }, positions);
         }
+    }
 
-        Self { roads: deduplicated_names }
+    /// Lists every road under each of its alternate names as well, with the same
+    /// positions, instead of referring from the alternate names to the main name:
+    ///
+    /// ```no_run,ignore
+    /// Castle Road (alternate name: Ffordd y Castell) -> [B2]
+    /// ```
+    ///
+    /// becomes:
+    ///
+    /// ```no_run,ignore
+    /// Castle Road -> [B2]
+    /// Ffordd y Castell -> [B2]
+    /// ```
+    pub fn duplicate_alternate_names(&mut self) {
+        let alternate_names = ::std::mem::take(&mut self.alternate_names);
+        for (name, alternates) in alternate_names {
+            let positions = self.roads.get(&name).cloned().unwrap_or_default();
+            let house_numbers = self.house_numbers.get(&name).cloned();
+            for alternate in alternates {
+                let alternate_name = IndexName { street_name: alternate, .. name.clone() };
+                if let Some(house_numbers) = &house_numbers {
+                    self.house_numbers.insert(alternate_name.clone(), house_numbers.clone());
+                }
+                self.roads
+                .entry(alternate_name)
+                .or_default()
+                .extend(positions.iter().cloned());
+            }
+        }
     }
 
     /// Processes road names (`[A1, A2]` => `A1-A2`) if they span less than 2 grids.
@@ -80,22 +438,33 @@ impl DeduplicatedRoads {
     /// 
     /// Because of this limitation `process()` gives you two types of roads back: 
     /// - `ProcessedRoadName` is for roads that span only 1 or 2 grid cells 
-    /// (i.e. `"Canterbury Road" => A9`, `"Canterbury Road" => A9-A10`).
-    /// In these cases (which cover 90% of street index names), the mapping is not
-    /// ambigouus.
+    ///   (i.e. `"Canterbury Road" => A9`, `"Canterbury Road" => A9-A10`).
+    ///   In these cases (which cover 90% of street index names), the mapping is not
+    ///   ambigouus.
     /// 
     /// `UnprocessedRoadName` is for anything else (e.g. `"Canterbury Road" => [A9, A10, E1, E2]`. 
     /// Usually these roads need to be manually reviewed - it could likely be that 
     /// there are two roads `"Canterbury Road" => A9-10;E1-E2`, but it could also
     /// be that the road is just one road and part of it is just clipped off the map,
     /// in which case you'd write `"Canterbury Road" => A9-E2`. 
+    ///
+    /// For every alternate name, a `CrossReference` to the main name is added
+    /// to the processed roads (see `duplicate_alternate_names` for the alternative).
+    ///
+    /// Roads with house numbers (see `add_house_numbers`) get one house number
+    /// range per cell, for both sides of the street (see `process_with_sides`).
     pub fn process(&self) -> (ProcessedRoadNames, UnprocessedRoadNames) {
+        self.process_with_sides(HouseNumberSides::Combined)
+    }
+
+    /// Same as `process`, but the house number ranges are combined according to `sides`
+    pub fn process_with_sides(&self, sides: HouseNumberSides) -> (ProcessedRoadNames, UnprocessedRoadNames) {
 
         let mut processed = BTreeMap::new();
         let mut unprocessed = BTreeMap::new();
 
         for (road_name, positions) in &self.roads {
-            let positions_vec = positions.into_iter().cloned().collect::<Vec<GridPosition>>();
+            let positions_vec = positions.iter().cloned().collect::<Vec<GridPosition>>();
             match positions_vec.len() {
                 0 => { },
                 1 => { processed.insert(road_name.clone(), FinalizedGridPositon::SingleRect(positions_vec[0].clone())); }
@@ -104,15 +473,204 @@ impl DeduplicatedRoads {
             }
         }
 
+        let cross_references = self.alternate_names.iter().flat_map(|(name, alternates)| {
+            alternates.iter()
+            .filter(move |alternate| **alternate != name.street_name)
+            .map(move |alternate| CrossReference {
+                name: IndexName { street_name: alternate.clone(), .. name.clone() },
+                target: name.clone(),
+            })
+        }).collect();
+
         (ProcessedRoadNames {
-            processed: processed.into_iter().map(|(k, v)| ProcessedRoad { name: k, position: v }).collect(),
+            processed: processed.into_iter().map(|(k, v)| ProcessedRoad { house_numbers: self.house_number_ranges(&k, sides), name: k, position: v }).collect(),
+            cross_references,
         },
         UnprocessedRoadNames {
-            unprocessed: unprocessed.into_iter().map(|(k, v)| UnprocessedRoad { name: k, positions: v }).collect(),
+            unprocessed: unprocessed.into_iter().map(|(k, v)| UnprocessedRoad { house_numbers: self.house_number_ranges(&k, sides), name: k, positions: v }).collect(),
         })
     }
 }
 
+/// How the entries of an index are sorted
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SortOrder {
+    /// By name, case-sensitive (the default)
+    ByName,
+    /// By name, ignoring upper / lower case
+    ByNameIgnoreCase,
+    /// By the (first) grid position, then by name. Useful for
+    /// short lists such as points of interest
+    ByPosition,
+}
+
+/// Sort and export settings for the index of one `Category`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexSettings {
+    /// Heading of the index, i.e. `"Streets"`
+    pub title: String,
+    pub sort: SortOrder,
+    /// Delimiter used by `ProcessedIndex::to_csv`
+    pub delimiter: String,
+    pub locality_qualifier: LocalityQualifier,
+    pub alternate_names: AlternateNames,
+    pub house_numbers: HouseNumberSides,
+}
+
+impl IndexSettings {
+    /// Default settings for a category: the category name as the title,
+    /// sorted by name, delimited by a TAB character, always showing localities,
+    /// alternate names as cross references, one house number range for both sides
+    pub fn new(category: &Category) -> Self {
+        Self {
+            title: category.to_string(),
+            sort: SortOrder::ByName,
+            delimiter: String::from("\t"),
+            locality_qualifier: LocalityQualifier::Always,
+            alternate_names: AlternateNames::CrossReference,
+            house_numbers: HouseNumberSides::Combined,
+        }
+    }
+}
+
+/// Deduplicated roads, partitioned into a separate index per category
+/// (streets, places, points of interest, ...), each with its own settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CategorizedRoads {
+    pub indexes: BTreeMap<Category, DeduplicatedRoads>,
+    /// Settings for each category. If a category has no settings,
+    /// `IndexSettings::new(category)` is used.
+    pub settings: BTreeMap<Category, IndexSettings>,
+}
+
+impl CategorizedRoads {
+    /// Partitions the streets by their category and deduplicates each partition
+    /// (see `DeduplicatedRoads::from_streets`)
+    pub fn from_streets(streets: &[InputStreetValue]) -> Self {
+        let mut partitions = BTreeMap::new();
+
+        for input_street in streets {
+            partitions
+            .entry(input_street.category.clone())
+            .or_insert_with(Vec::new)
+            .push(input_street.clone());
+        }
+
+        Self {
+            indexes: partitions.into_iter().map(|(category, streets)| (category, DeduplicatedRoads::from_streets(&streets))).collect(),
+            settings: BTreeMap::new(),
+        }
+    }
+
+    /// Adds the house numbers of address points to the roads of all indexes,
+    /// see `DeduplicatedRoads::add_house_numbers`
+    pub fn add_house_numbers(&mut self, addresses: &[InputAddress]) {
+        for roads in self.indexes.values_mut() {
+            roads.add_house_numbers(addresses);
+        }
+    }
+
+    /// Returns the settings for the index of the category
+    pub fn settings(&self, category: &Category) -> IndexSettings {
+        self.settings.get(category).cloned().unwrap_or_else(|| IndexSettings::new(category))
+    }
+
+    /// Processes each index (see `DeduplicatedRoads::process`) and sorts it
+    /// according to its settings. The indexes are ordered by category.
+    pub fn process(&self) -> Vec<ProcessedIndex> {
+        self.indexes.iter().map(|(category, roads)| {
+            let settings = self.settings(category);
+            let mut roads = roads.clone();
+            if settings.locality_qualifier == LocalityQualifier::OnlyWhenDuplicated {
+                roads.remove_unique_localities();
+            }
+            match settings.alternate_names {
+                AlternateNames::CrossReference => { },
+                AlternateNames::Duplicate => roads.duplicate_alternate_names(),
+                AlternateNames::Ignore => roads.alternate_names.clear(),
+            }
+            let (mut processed, mut unprocessed) = roads.process_with_sides(settings.house_numbers);
+            processed.sort(settings.sort);
+            unprocessed.sort(settings.sort);
+            ProcessedIndex {
+                category: category.clone(),
+                settings,
+                processed,
+                unprocessed,
+            }
+        }).collect()
+    }
+}
+
+/// Processed index of one category, see `CategorizedRoads::process`
+pub struct ProcessedIndex {
+    pub category: Category,
+    pub settings: IndexSettings,
+    pub processed: ProcessedRoadNames,
+    pub unprocessed: UnprocessedRoadNames,
+}
+
+impl ProcessedIndex {
+    /// Exports the (processed, unprocessed) roads as CSV,
+    /// using the delimiter from the settings of the index
+    pub fn to_csv(&self) -> (String, String) {
+        (self.processed.to_csv(&self.settings.delimiter), self.unprocessed.to_csv(&self.settings.delimiter))
+    }
+
+    /// Returns the lines of the whole index for printing: the processed roads
+    /// with the unprocessed roads (with all of their positions) inserted at their
+    /// alphabetical position, see `ProcessedRoadNames::entries`. Usually the
+    /// unprocessed roads should be reviewed before printing the index.
+    pub fn entries(&self) -> Vec<IndexEntry> {
+        merge_entries(self.processed.entries(), self.unprocessed.entries())
+    }
+
+    /// Applies the decisions of an editor about the unprocessed roads: roads that
+    /// are listed at one or two cells are moved to the processed roads (with the
+    /// house number ranges of these cells), removed roads are deleted together
+    /// with the cross references pointing to them. Roads without a decision stay
+    /// unprocessed. The roads are sorted again according to the settings.
+    pub fn apply_review(&mut self, resolutions: &[ReviewResolution]) {
+        let mut removed = Vec::new();
+
+        for road in ::std::mem::take(&mut self.unprocessed.unprocessed) {
+            match resolutions.iter().find(|resolution| resolution.name == road.name).map(|resolution| &resolution.decision) {
+                None => self.unprocessed.unprocessed.push(road),
+                Some(ReviewDecision::Remove) => removed.push(road.name),
+                Some(ReviewDecision::Position(position)) => {
+                    let house_numbers = road.house_numbers.into_iter().filter(|range| match position {
+                        FinalizedGridPositon::SingleRect(a) => range.position == *a,
+                        FinalizedGridPositon::TwoRect(a, b) => range.position == *a || range.position == *b,
+                    }).collect();
+                    self.processed.processed.push(ProcessedRoad { name: road.name, position: position.clone(), house_numbers });
+                },
+            }
+        }
+
+        self.processed.cross_references.retain(|reference| !removed.contains(&reference.target));
+        self.processed.sort(self.settings.sort);
+        self.unprocessed.sort(self.settings.sort);
+    }
+}
+
+/// Inserts each of the `others` before the first of the `entries` that comes
+/// after it alphabetically (ignoring case), keeping the order of both lists
+fn merge_entries(entries: Vec<IndexEntry>, others: Vec<IndexEntry>) -> Vec<IndexEntry> {
+    let mut merged = Vec::with_capacity(entries.len() + others.len());
+    let mut others = others.into_iter().peekable();
+
+    for entry in entries {
+        let name = entry.name.to_string().to_lowercase();
+        while let Some(other) = others.next_if(|other| other.name.to_string().to_lowercase() < name) {
+            merged.push(other);
+        }
+        merged.push(entry);
+    }
+
+    merged.extend(others);
+    merged
+}
+
 #[test]
 fn test_deduplicate_streets() {
     let input = [
@@ -121,21 +679,33 @@ fn test_deduplicate_streets() {
             position: GridPosition {
                 column: String::from("A"),
                 row: 4,
-            }
+            },
+            category: Category::Street,
+            symbol: None,
+            locality: None,
+            alternate_names: Vec::new(),
         },
         InputStreetValue {
             street_name: StreetName(String::from("Valley View Road")),
             position: GridPosition {
                 column: String::from("A"),
                 row: 5,
-            }
+            },
+            category: Category::Street,
+            symbol: None,
+            locality: None,
+            alternate_names: Vec::new(),
         },
         InputStreetValue {
             street_name: StreetName(String::from("Valley View Road")),
             position: GridPosition {
                 column: String::from("B"),
                 row: 6,
-            }
+            },
+            category: Category::Street,
+            symbol: None,
+            locality: None,
+            alternate_names: Vec::new(),
         },
     ];
 
@@ -145,9 +715,161 @@ fn test_deduplicate_streets() {
     valley_view_road_expected.insert(GridPosition { column: String::from("A"), row: 4 });
     valley_view_road_expected.insert(GridPosition { column: String::from("A"), row: 5 });
     valley_view_road_expected.insert(GridPosition { column: String::from("B"), row: 6 });
-    output_expected.insert(StreetName(String::from("Valley View Road")), valley_view_road_expected);
+    output_expected.insert(IndexName::from(StreetName(String::from("Valley View Road"))), valley_view_road_expected);
+
+    assert_eq!(DeduplicatedRoads::from_streets(&input), DeduplicatedRoads { roads: output_expected, alternate_names: BTreeMap::new(), house_numbers: BTreeMap::new() });
+}
+
+#[test]
+fn test_categorized_roads() {
+    let input = |name: &str, column: &str, row, category| InputStreetValue {
+        category,
+        .. InputStreetValue::new(name, GridPosition { column: String::from(column), row })
+    };
+
+    let mut categorized = CategorizedRoads::from_streets(&[
+        input("Valley View Road", "A", 4, Category::Street),
+        input("canterbury Road", "B", 2, Category::Street),
+        input("St. Mary's Hospital", "AA", 1, Category::PointOfInterest),
+        input("Central Station", "C", 4, Category::PointOfInterest),
+    ]);
+
+    categorized.settings.insert(Category::Street, IndexSettings {
+        sort: SortOrder::ByNameIgnoreCase,
+        delimiter: String::from(";"),
+        .. IndexSettings::new(&Category::Street)
+    });
+    categorized.settings.insert(Category::PointOfInterest, IndexSettings {
+        sort: SortOrder::ByPosition,
+        .. IndexSettings::new(&Category::PointOfInterest)
+    });
+
+    let indexes = categorized.process();
+    assert_eq!(indexes.len(), 2);
+
+    assert_eq!(indexes[0].category, Category::Street);
+    assert_eq!(indexes[0].to_csv().0, "canterbury Road;B2\r\nValley View Road;A4");
+
+    assert_eq!(indexes[1].settings.title, "Points of interest");
+    assert_eq!(indexes[1].to_csv().0, "Central Station\tC4\r\nSt. Mary's Hospital\tAA1");
+}
+
+#[test]
+fn test_deduplicate_symbols() {
+    let input = |symbol: &str, row| InputStreetValue {
+        category: Category::PointOfInterest,
+        symbol: Some(String::from(symbol)),
+        .. InputStreetValue::new("Central Station", GridPosition { column: String::from("C"), row })
+    };
+
+    let (processed, _) = DeduplicatedRoads::from_streets(&[input("rail", 4), input("bus", 5), input("rail", 5)]).process();
+    assert_eq!(processed.to_csv("\t"), "Central Station \u{27E8}bus\u{27E9}\tC5\r\nCentral Station \u{27E8}rail\u{27E9}\tC4-C5");
+}
+
+#[test]
+fn test_locality_qualifier() {
+    let input = |name: &str, locality: &str, column: &str| InputStreetValue {
+        locality: Some(String::from(locality)),
+        .. InputStreetValue::new(name, GridPosition { column: String::from(column), row: 2 })
+    };
+
+    let mut categorized = CategorizedRoads::from_streets(&[
+        input("Hauptstraße", "Altdorf", "B"),
+        input("Hauptstraße", "Neudorf", "F"),
+        input("Kirchweg", "Altdorf", "C"),
+    ]);
+
+    let csv = categorized.process()[0].to_csv().0;
+    assert_eq!(csv, "Hauptstraße (Altdorf)\tB2\r\nHauptstraße (Neudorf)\tF2\r\nKirchweg (Altdorf)\tC2");
+
+    categorized.settings.insert(Category::Street, IndexSettings {
+        locality_qualifier: LocalityQualifier::OnlyWhenDuplicated,
+        .. IndexSettings::new(&Category::Street)
+    });
+
+    let csv = categorized.process()[0].to_csv().0;
+    assert_eq!(csv, "Hauptstraße (Altdorf)\tB2\r\nHauptstraße (Neudorf)\tF2\r\nKirchweg\tC2");
+}
+
+#[test]
+fn test_alternate_names() {
+    let input = |name: &str, alternate: Option<&str>, column: &str| InputStreetValue {
+        alternate_names: alternate.map(|a| StreetName(String::from(a))).into_iter().collect(),
+        .. InputStreetValue::new(name, GridPosition { column: String::from(column), row: 2 })
+    };
+
+    let mut categorized = CategorizedRoads::from_streets(&[
+        input("Castle Road", Some("Ffordd y Castell"), "B"),
+        input("Castle Road", None, "C"),
+        input("High Street", Some("Stryd Fawr"), "A"),
+        input("Mill Lane", None, "F"),
+    ]);
+
+    let csv = categorized.process()[0].to_csv().0;
+    assert_eq!(csv, "Castle Road\tB2-C2\r\nFfordd y Castell\t\u{2192} see Castle Road\r\nHigh Street\tA2\r\nMill Lane\tF2\r\nStryd Fawr\t\u{2192} see High Street");
+
+    categorized.settings.insert(Category::Street, IndexSettings {
+        alternate_names: AlternateNames::Duplicate,
+        .. IndexSettings::new(&Category::Street)
+    });
 
-    assert_eq!(DeduplicatedRoads::from_streets(&input), DeduplicatedRoads { roads: output_expected });
+    let csv = categorized.process()[0].to_csv().0;
+    assert_eq!(csv, "Castle Road\tB2-C2\r\nFfordd y Castell\tB2-C2\r\nHigh Street\tA2\r\nMill Lane\tF2\r\nStryd Fawr\tA2");
+}
+
+#[test]
+fn test_house_numbers() {
+    let position = |column: &str| GridPosition { column: String::from(column), row: 2 };
+    let mut roads = DeduplicatedRoads::from_streets(&[
+        InputStreetValue::new("Canterbury Road", position("B")),
+        InputStreetValue::new("Canterbury Road", position("C")),
+        InputStreetValue::new("Mill Lane", position("B")),
+        InputStreetValue::new("Mill Lane", position("C")),
+    ]);
+
+    let address = |street_name: &str, number: u32, column: &str| InputAddress {
+        street_name: StreetName(String::from(street_name)),
+        locality: None,
+        house_number: number,
+        position: position(column),
+    };
+    // the address in D2 is not at a cell of the road
+    let mut addresses = [(1, "B"), (47, "B"), (2, "B"), (48, "C"), (120, "C"), (49, "C"), (122, "D")].iter()
+        .map(|(number, column)| address("Canterbury Road", *number, column))
+        .collect::<Vec<_>>();
+    addresses.push(address("Mill Lane", 1, "B"));
+    addresses.push(address("Mill Lane", 47, "B"));
+    roads.add_house_numbers(&addresses);
+
+    let (processed, _) = roads.process();
+    assert_eq!(processed.to_csv("\t"), "Canterbury Road\t1-47 B2, 48-120 C2\r\nMill Lane\t1-47 B2, C2");
+
+    let (processed, _) = roads.process_with_sides(HouseNumberSides::OddEven);
+    assert_eq!(processed.to_csv("\t"), "Canterbury Road\t1-47 (odd) B2, 49 (odd) C2, 2 (even) B2, 48-120 (even) C2\r\nMill Lane\t1-47 (odd) B2, C2");
+}
+
+#[test]
+fn test_apply_review() {
+    let input = |name: &str, alternate: Option<&str>, column: &str| InputStreetValue {
+        alternate_names: alternate.map(|a| StreetName(String::from(a))).into_iter().collect(),
+        .. InputStreetValue::new(name, GridPosition { column: String::from(column), row: 2 })
+    };
+    let streets = ["A", "B", "C"].iter().flat_map(|column| vec![
+        input("Long Road", None, column),
+        input("Old Road", Some("Ffordd Hen"), column),
+        input("Ring Road", None, column),
+    ]).collect::<Vec<_>>();
+    let mut index = CategorizedRoads::from_streets(&streets).process().remove(0);
+
+    let name = |name: &str| IndexName::from(StreetName(String::from(name)));
+    index.apply_review(&[
+        ReviewResolution { name: name("Long Road"), decision: ReviewDecision::parse(" b2-c2 ").unwrap() },
+        ReviewResolution { name: name("Old Road"), decision: ReviewDecision::parse("Remove").unwrap() },
+    ]);
+
+    assert_eq!(index.to_csv(), (String::from("Long Road\tB2-C2"), String::from("Ring Road\tA2\tB2\tC2")));
+    assert_eq!(ReviewDecision::parse("B0"), None);
+    assert_eq!(ReviewDecision::parse("maybe"), None);
 }
 
 #[test]
@@ -161,6 +883,7 @@ fn test_format_street() {
 }
 
 /// Wrapper for grid positions that span less than 2 grid cells
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FinalizedGridPositon {
     /// Road is contained within a single rect, i.e. "Valley Road -> A6"
     SingleRect(GridPosition),
@@ -168,6 +891,17 @@ pub enum FinalizedGridPositon {
     TwoRect(GridPosition, GridPosition),
 }
 
+impl FinalizedGridPositon {
+    /// Returns the first grid position (the only one for `SingleRect`)
+    pub fn first(&self) -> &GridPosition {
+        use self::FinalizedGridPositon::*;
+        match self {
+            SingleRect(single) => single,
+            TwoRect(a, _) => a,
+        }
+    }
+}
+
 impl fmt::Display for FinalizedGridPositon {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use self::FinalizedGridPositon::*;
@@ -182,20 +916,62 @@ impl fmt::Display for FinalizedGridPositon {
 
 /// Road name that spans less than 2 grid cells
 pub struct ProcessedRoad {
-    pub name: StreetName,
+    pub name: IndexName,
     pub position: FinalizedGridPositon,
+    /// House number ranges per cell, empty if the road has no address points
+    pub house_numbers: Vec<HouseNumberRange>,
+}
+
+impl ProcessedRoad {
+    /// Returns the reference of the road as listed in the index: the house number
+    /// ranges followed by the cells without house numbers (`"1-47 B2, B3"`) or,
+    /// if there are no house numbers, the position (`"B2-B3"`)
+    pub fn reference(&self) -> String {
+        if self.house_numbers.is_empty() {
+            self.position.to_string()
+        } else {
+            let positions = match &self.position {
+                FinalizedGridPositon::SingleRect(a) => vec![a.clone()],
+                FinalizedGridPositon::TwoRect(a, b) => vec![a.clone(), b.clone()],
+            };
+            house_number_reference(&self.house_numbers, &positions)
+        }
+    }
 }
 
 impl fmt::Display for ProcessedRoad {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}\t{}", self.name, self.position)
+        write!(f, "{}\t{}", self.name, self.reference())
     }
 }
 
 /// Road name that spans more than 2 grid cells
 pub struct UnprocessedRoad {
-    pub name: StreetName,
+    pub name: IndexName,
     pub positions: Vec<GridPosition>,
+    /// House number ranges per cell, empty if the road has no address points
+    pub house_numbers: Vec<HouseNumberRange>,
+}
+
+impl UnprocessedRoad {
+    /// Returns the reference of the road as listed in the index: the house number
+    /// ranges followed by the cells without house numbers or, if there are no
+    /// house numbers, all positions (`"A9, A10, E1, E2"`)
+    pub fn reference(&self) -> String {
+        if self.house_numbers.is_empty() {
+            self.positions.iter().map(|pos| pos.to_string()).collect::<Vec<String>>().join(", ")
+        } else {
+            house_number_reference(&self.house_numbers, &self.positions)
+        }
+    }
+}
+
+/// House number ranges, followed by the positions that have no range: `"1-47 B2, B3"`
+fn house_number_reference(ranges: &[HouseNumberRange], positions: &[GridPosition]) -> String {
+    ranges.iter().map(|range| range.to_string())
+    .chain(positions.iter().filter(|position| !ranges.iter().any(|range| range.position == **position)).map(|position| position.to_string()))
+    .collect::<Vec<String>>()
+    .join(", ")
 }
 
 impl fmt::Display for UnprocessedRoad {
@@ -205,15 +981,114 @@ impl fmt::Display for UnprocessedRoad {
     }
 }
 
+/// Row pointing from an alternate name of a street to the name the street
+/// is listed under: `"Ffordd y Castell → see Castle Road"`
+pub struct CrossReference {
+    pub name: IndexName,
+    pub target: IndexName,
+}
+
+impl CrossReference {
+    /// Returns the reference as listed in the index: `"→ see Castle Road"`
+    pub fn reference(&self) -> String {
+        format!("\u{2192} see {}", self.target)
+    }
+}
+
+impl fmt::Display for CrossReference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}\t{}", self.name, self.reference())
+    }
+}
+
+/// Decision of an editor about an unprocessed road, see `ProcessedIndex::apply_review`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReviewDecision {
+    /// List the road at the main one or two cells: `"B2"` or `"B2-C3"`
+    Position(FinalizedGridPositon),
+    /// Remove the road from the index: `"remove"`
+    Remove,
+}
+
+impl ReviewDecision {
+    /// Parses a decision as written by the editor (`"B2"`, `"B2-C3"` or `"remove"`, ignoring case)
+    pub fn parse(decision: &str) -> Option<Self> {
+        let decision = decision.trim().to_uppercase();
+        if decision == "REMOVE" {
+            return Some(ReviewDecision::Remove);
+        }
+        match decision.split_once('-') {
+            Some((a, b)) => Some(ReviewDecision::Position(FinalizedGridPositon::TwoRect(GridPosition::parse(a)?, GridPosition::parse(b)?))),
+            None => Some(ReviewDecision::Position(FinalizedGridPositon::SingleRect(GridPosition::parse(&decision)?))),
+        }
+    }
+}
+
+/// Decision of an editor about the unprocessed road with the name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReviewResolution {
+    pub name: IndexName,
+    pub decision: ReviewDecision,
+}
+
+/// One line of the finished index: the name and the reference as it should be
+/// printed, i.e. `"B2-B3"`, `"1-47 B2, 48-120 B3"` or `"→ see Castle Road"`.
+/// Used by the exporters, see `ProcessedRoadNames::entries`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub name: IndexName,
+    pub reference: String,
+}
+
+impl IndexEntry {
+    /// Entry for a street name without locality or symbol
+    pub fn new(street_name: &str, reference: &str) -> Self {
+        Self {
+            name: IndexName::from(StreetName(street_name.to_string())),
+            reference: reference.to_string(),
+        }
+    }
+}
+
 /// Simple wrapper for `Vec<ProcessedRoad>` with `.to_csv()` exporting function
 pub struct ProcessedRoadNames {
     pub processed: Vec<ProcessedRoad>,
+    /// Rows for the alternate names of the roads, see `AlternateNames`
+    pub cross_references: Vec<CrossReference>,
 }
 
 impl ProcessedRoadNames {
+    /// Sorts the roads, i.e. for roads that were merged from several indexes
+    pub fn sort(&mut self, order: SortOrder) {
+        match order {
+            SortOrder::ByName => self.processed.sort_by(|a, b| a.name.cmp(&b.name)),
+            SortOrder::ByNameIgnoreCase => self.processed.sort_by_key(|road| road.name.to_string().to_lowercase()),
+            SortOrder::ByPosition => self.processed.sort_by(|a, b| {
+                a.position.first().grid_order().cmp(&b.position.first().grid_order()).then_with(|| a.name.cmp(&b.name))
+            }),
+        }
+        // Cross references have no position, so they are always sorted by name
+        match order {
+            SortOrder::ByName | SortOrder::ByPosition => self.cross_references.sort_by(|a, b| a.name.cmp(&b.name)),
+            SortOrder::ByNameIgnoreCase => self.cross_references.sort_by_key(|reference| reference.name.to_string().to_lowercase()),
+        }
+    }
+
+    /// Returns the lines of the index in the order of the roads. The cross references
+    /// are inserted before the first road that comes after them alphabetically
+    /// (ignoring case), so for roads that are sorted by position, they end up
+    /// at the end of the list.
+    pub fn entries(&self) -> Vec<IndexEntry> {
+        merge_entries(
+            self.processed.iter().map(|road| IndexEntry { name: road.name.clone(), reference: road.reference() }).collect(),
+            self.cross_references.iter().map(|reference| IndexEntry { name: reference.name.clone(), reference: reference.reference() }).collect(),
+        )
+    }
+
+    /// Exports the roads as CSV, see `entries` for the order of the lines
     pub fn to_csv(&self, delimiter: &str) -> String {
-        self.processed.iter().map(|processed_road| 
-            format!("{}{}{}", processed_road.name, delimiter, processed_road.position))
+        self.entries().iter()
+        .map(|entry| format!("{}{}{}", entry.name, delimiter, entry.reference))
         .collect::<Vec<String>>()
         .join("\r\n")
     }
@@ -225,6 +1100,24 @@ pub struct UnprocessedRoadNames {
 }
 
 impl UnprocessedRoadNames {
+    /// Sorts the roads, i.e. for roads that were merged from several indexes
+    pub fn sort(&mut self, order: SortOrder) {
+        match order {
+            SortOrder::ByName => self.unprocessed.sort_by(|a, b| a.name.cmp(&b.name)),
+            SortOrder::ByNameIgnoreCase => self.unprocessed.sort_by_key(|road| road.name.to_string().to_lowercase()),
+            SortOrder::ByPosition => self.unprocessed.sort_by(|a, b| {
+                let first = |road: &UnprocessedRoad| road.positions.first().map(|p| (p.column.len(), p.column.clone(), p.row));
+                first(a).cmp(&first(b)).then_with(|| a.name.cmp(&b.name))
+            }),
+        }
+    }
+
+    /// Returns the lines of the index in the order of the roads, with all
+    /// positions of each road (see `UnprocessedRoad::reference`)
+    pub fn entries(&self) -> Vec<IndexEntry> {
+        self.unprocessed.iter().map(|road| IndexEntry { name: road.name.clone(), reference: road.reference() }).collect()
+    }
+
     pub fn to_csv(&self, delimiter: &str) -> String {
         self.unprocessed.iter().map(|unprocessed_road| {
             let unprocessed_string = unprocessed_road.positions
@@ -0,0 +1,248 @@
+//! XLSX workbooks for the editorial review of an index (`xlsx` feature)
+//!
+//! `ProcessedIndex::to_xlsx` writes a workbook with a "final" sheet of the
+//! processed roads and a "review" sheet of the unprocessed roads, with one cell
+//! per position. The editor fills in the "Decision" column (`"B2"`, `"B2-C3"`
+//! or `"remove"`) and `read_review` reads the decisions back, ready for
+//! `ProcessedIndex::apply_review`.
+
+use std::{fmt, error::Error, io::Cursor};
+use rust_xlsxwriter::{Workbook, Worksheet, Format, DataValidation, XlsxError};
+use calamine::{self, Reader, Data, open_workbook_auto_from_rs};
+use roads2csv::{ProcessedIndex, IndexName, StreetName, GridPosition, FinalizedGridPositon, ReviewDecision, ReviewResolution};
+
+/// Name of the sheet with the processed roads
+pub const FINAL_SHEET: &str = "final";
+/// Name of the sheet with the unprocessed roads
+pub const REVIEW_SHEET: &str = "review";
+/// Column of the decisions in the review sheet (starting at 0)
+const DECISION_COLUMN: u16 = 3;
+
+/// Error while writing or reading a review workbook
+#[derive(Debug)]
+pub enum SpreadsheetError {
+    /// Error of the XLSX writer
+    Xlsx(XlsxError),
+    /// The workbook could not be read or has no review sheet
+    Read(calamine::Error),
+    /// The decision in a row of the review sheet (starting at 1, as shown in
+    /// the spreadsheet) is neither a position nor `"remove"`
+    InvalidDecision { row: usize, decision: String },
+    /// The decision in a row lists a cell that is not one of the positions
+    /// of the road in that row, i.e. a typo such as `"Z99"`
+    UnknownPosition { row: usize, position: String },
+}
+
+impl fmt::Display for SpreadsheetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpreadsheetError::Xlsx(e) => write!(f, "could not write XLSX: {}", e),
+            SpreadsheetError::Read(e) => write!(f, "could not read workbook: {}", e),
+            SpreadsheetError::InvalidDecision { row, decision } => write!(f, "invalid decision in row {}: \"{}\"", row, decision),
+            SpreadsheetError::UnknownPosition { row, position } => write!(f, "decision in row {} lists {}, which is not a position of the road", row, position),
+        }
+    }
+}
+
+impl Error for SpreadsheetError { }
+
+impl From<XlsxError> for SpreadsheetError {
+    fn from(e: XlsxError) -> Self {
+        SpreadsheetError::Xlsx(e)
+    }
+}
+
+impl From<calamine::Error> for SpreadsheetError {
+    fn from(e: calamine::Error) -> Self {
+        SpreadsheetError::Read(e)
+    }
+}
+
+impl ProcessedIndex {
+    /// Writes the index as an XLSX workbook with the sheets `"final"`
+    /// (name, locality, symbol, reference of the processed roads and cross
+    /// references) and `"review"` (name, locality, symbol, decision and one
+    /// cell per position of the unprocessed roads)
+    pub fn to_xlsx(&self) -> Result<Vec<u8>, SpreadsheetError> {
+        let mut workbook = Workbook::new();
+        let bold = Format::new().set_bold();
+
+        let sheet = workbook.add_worksheet().set_name(FINAL_SHEET)?;
+        write_header(sheet, &["Name", "Locality", "Symbol", "Reference"], &bold)?;
+        for (row, entry) in self.processed.entries().iter().enumerate() {
+            let row = row as u32 + 1;
+            write_name(sheet, row, &entry.name)?;
+            sheet.write_string(row, 3, &entry.reference)?;
+        }
+
+        let sheet = workbook.add_worksheet().set_name(REVIEW_SHEET)?;
+        let positions = self.unprocessed.unprocessed.iter().map(|road| road.positions.len()).max().unwrap_or(0);
+        let mut header = vec![String::from("Name"), String::from("Locality"), String::from("Symbol"), String::from("Decision")];
+        header.extend((1..=positions).map(|i| format!("Position {}", i)));
+        write_header(sheet, &header.iter().map(|h| h.as_str()).collect::<Vec<&str>>(), &bold)?;
+        for (row, road) in self.unprocessed.unprocessed.iter().enumerate() {
+            let row = row as u32 + 1;
+            write_name(sheet, row, &road.name)?;
+            for (column, position) in road.positions.iter().enumerate() {
+                sheet.write_string(row, DECISION_COLUMN + 1 + column as u16, position.to_string())?;
+            }
+        }
+
+        if !self.unprocessed.unprocessed.is_empty() {
+            let hint = DataValidation::new()
+                .allow_any_value()
+                .set_input_title("Decision")?
+                .set_input_message("\"B2\" or \"B2-C3\" to list the road at these cells, \"remove\" to remove it, empty to keep all positions")?;
+            sheet.add_data_validation(1, DECISION_COLUMN, self.unprocessed.unprocessed.len() as u32, DECISION_COLUMN, &hint)?;
+        }
+
+        Ok(workbook.save_to_buffer()?)
+    }
+}
+
+/// Writes the bold header row and freezes it
+fn write_header(sheet: &mut Worksheet, header: &[&str], bold: &Format) -> Result<(), XlsxError> {
+    for (column, title) in header.iter().enumerate() {
+        sheet.write_string_with_format(0, column as u16, *title, bold)?;
+    }
+    sheet.set_column_width(0, 30)?;
+    sheet.set_freeze_panes(1, 0)?;
+    Ok(())
+}
+
+/// Writes the name, locality and symbol into the first three columns
+fn write_name(sheet: &mut Worksheet, row: u32, name: &IndexName) -> Result<(), XlsxError> {
+    sheet.write_string(row, 0, &name.street_name.0)?;
+    if let Some(locality) = &name.locality {
+        sheet.write_string(row, 1, locality)?;
+    }
+    if let Some(symbol) = &name.symbol {
+        sheet.write_string(row, 2, symbol)?;
+    }
+    Ok(())
+}
+
+/// Reads the decisions of the editor from the review sheet of a workbook written
+/// by `ProcessedIndex::to_xlsx`. The workbook may also have been saved as XLS
+/// or ODS by the spreadsheet application. Rows without a decision are skipped.
+/// The cells of a decision have to be among the positions of the road in the
+/// same row, otherwise `SpreadsheetError::UnknownPosition` is returned.
+pub fn read_review(data: &[u8]) -> Result<Vec<ReviewResolution>, SpreadsheetError> {
+    let mut workbook = open_workbook_auto_from_rs(Cursor::new(data))?;
+    let range = workbook.worksheet_range(REVIEW_SHEET)?;
+    let mut resolutions = Vec::new();
+
+    // The range starts at the first used cell, which is the header (usually in
+    // A1, but the editor may have inserted rows or columns before it)
+    let first_row = range.start().map(|(row, _)| row as usize).unwrap_or(0);
+    let first_column = range.start().map(|(_, column)| column as usize).unwrap_or(0);
+
+    for (row_index, row) in range.rows().enumerate().skip(1) {
+        let cell = |column: usize| match column.checked_sub(first_column).and_then(|column| row.get(column)) {
+            None | Some(Data::Empty) => String::new(),
+            Some(data) => data.to_string().trim().to_string(),
+        };
+        let optional = |text: String| if text.is_empty() { None } else { Some(text) };
+
+        let decision = cell(DECISION_COLUMN as usize);
+        if decision.is_empty() || cell(0).is_empty() {
+            continue;
+        }
+
+        let row_number = first_row + row_index + 1;
+        let decision = ReviewDecision::parse(&decision).ok_or(SpreadsheetError::InvalidDecision {
+            row: row_number,
+            decision,
+        })?;
+
+        if let ReviewDecision::Position(position) = &decision {
+            let positions = (DECISION_COLUMN as usize + 1..first_column + row.len())
+                .filter_map(|column| GridPosition::parse(&cell(column)))
+                .collect::<Vec<GridPosition>>();
+            let cells = match position {
+                FinalizedGridPositon::SingleRect(a) => vec![a],
+                FinalizedGridPositon::TwoRect(a, b) => vec![a, b],
+            };
+            if let Some(unknown) = cells.into_iter().find(|cell| !positions.contains(cell)) {
+                return Err(SpreadsheetError::UnknownPosition { row: row_number, position: unknown.to_string() });
+            }
+        }
+
+        resolutions.push(ReviewResolution {
+            name: IndexName {
+                street_name: StreetName(cell(0)),
+                locality: optional(cell(1)),
+                symbol: optional(cell(2)),
+            },
+            decision,
+        });
+    }
+
+    Ok(resolutions)
+}
+
+#[test]
+fn test_review_workbook() {
+    use roads2csv::{CategorizedRoads, InputStreetValue};
+
+    let input = |name: &str, locality: Option<&str>, column: &str| InputStreetValue {
+        locality: locality.map(String::from),
+        .. InputStreetValue::new(name, GridPosition { column: String::from(column), row: 2 })
+    };
+    let streets = ["A", "B", "C"].iter().flat_map(|column| vec![
+        input("Long Road", None, column),
+        input("Ring Road", Some("Altdorf"), column),
+    ]).chain(vec![input("Short Road", None, "D")]).collect::<Vec<_>>();
+    let index = CategorizedRoads::from_streets(&streets).process().remove(0);
+
+    // The written workbook has no decisions yet
+    let xlsx = index.to_xlsx().unwrap();
+    assert_eq!(read_review(&xlsx).unwrap(), Vec::new());
+
+    // Workbook as returned by the editor
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet().set_name(REVIEW_SHEET).unwrap();
+    for (row, cells) in [
+        ["Name", "Locality", "Symbol", "Decision", "Position 1", "Position 2", "Position 3"],
+        ["Long Road", "", "", "B2-C2", "A2", "B2", "C2"],
+        ["Ring Road", "Altdorf", "", "remove", "A2", "B2", "C2"],
+    ].iter().enumerate() {
+        for (column, cell) in cells.iter().enumerate() {
+            sheet.write_string(row as u32, column as u16, *cell).unwrap();
+        }
+    }
+    let resolutions = read_review(&workbook.save_to_buffer().unwrap()).unwrap();
+    assert_eq!(resolutions, vec![
+        ReviewResolution {
+            name: IndexName::from(StreetName(String::from("Long Road"))),
+            decision: ReviewDecision::parse("B2-C2").unwrap(),
+        },
+        ReviewResolution {
+            name: IndexName { locality: Some(String::from("Altdorf")), .. IndexName::from(StreetName(String::from("Ring Road"))) },
+            decision: ReviewDecision::Remove,
+        },
+    ]);
+
+    // Header in the second row, below an empty row
+    let workbook = |decision: &str| {
+        let mut workbook = Workbook::new();
+        let sheet = workbook.add_worksheet().set_name(REVIEW_SHEET).unwrap();
+        sheet.write_string(1, 0, "Name").unwrap();
+        sheet.write_string(1, DECISION_COLUMN, "Decision").unwrap();
+        sheet.write_string(2, 0, "Long Road").unwrap();
+        sheet.write_string(2, DECISION_COLUMN, decision).unwrap();
+        sheet.write_string(2, DECISION_COLUMN + 1, "A2").unwrap();
+        sheet.write_string(2, DECISION_COLUMN + 2, "B2").unwrap();
+        workbook.save_to_buffer().unwrap()
+    };
+    let resolutions = read_review(&workbook("remove")).unwrap();
+    assert_eq!(resolutions.iter().map(|resolution| resolution.name.to_string()).collect::<Vec<_>>(), vec!["Long Road"]);
+    match read_review(&workbook("somewhere")) {
+        Err(SpreadsheetError::InvalidDecision { row: 3, .. }) => { },
+        other => panic!("expected an invalid decision in row 3, got {:?}", other),
+    }
+    match read_review(&workbook("A2-Z99")) {
+        Err(SpreadsheetError::UnknownPosition { row: 3, position }) => assert_eq!(position, "Z99"),
+        other => panic!("expected an unknown position in row 3, got {:?}", other),
+    }
+}
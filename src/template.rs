@@ -0,0 +1,314 @@
+//! Template-driven output of the index, for index lines in a custom format
+//!
+//! Instead of a new exporter for every client, the line of an entry is written
+//! as a template such as `"{name}{leader}{reference}"`, `"{name}, {reference}"`
+//! or `"{cells} {name}"`, with optional header and footer templates per letter
+//! section (i.e. `"== {letter} =="`).
+
+use std::{fmt, error::Error};
+use std::collections::BTreeMap;
+use roads2csv::{ProcessedIndex, GridPosition, FinalizedGridPositon, IndexEntry};
+use grouping::LetterGrouping;
+
+/// Error while parsing a template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    /// The name in braces is not one of the fields, see `TemplateField`
+    UnknownField(String),
+    /// A `{` without a closing `}` or a `}` without an opening `{`
+    /// (use `{{` and `}}` for literal braces)
+    UnmatchedBrace,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TemplateError::UnknownField(field) => write!(f, "unknown template field \"{{{}}}\"", field),
+            TemplateError::UnmatchedBrace => write!(f, "unmatched brace in template (use {{{{ and }}}} for literal braces)"),
+        }
+    }
+}
+
+impl Error for TemplateError { }
+
+/// Field that is replaced in a template
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TemplateField {
+    /// `{name}`: name as listed in the index, with locality and symbol: `"Hauptstraße (Altdorf)"`
+    Name,
+    /// `{street}`: only the street name: `"Hauptstraße"`
+    Street,
+    /// `{locality}`: the locality, empty if there is none
+    Locality,
+    /// `{symbol}`: the symbol, empty if there is none
+    Symbol,
+    /// `{category}`: the category of the index: `"Streets"`
+    Category,
+    /// `{title}`: the title of the index, see `IndexSettings::title`
+    Title,
+    /// `{reference}`: reference as printed in the index: `"B2-B3"`, `"1-47 B2"` or `"→ see Castle Road"`
+    Reference,
+    /// `{cells}`: the grid cells of the road: `"B2, B3"` (empty for cross references)
+    Cells,
+    /// `{page}`: the pages that the cells of the road are on, see `TemplateExport::pages`
+    Page,
+    /// `{letter}`: the initial letter of the section
+    Letter,
+    /// `{leader}`: fills the line with the leader character up to the line width,
+    /// see `TemplateExport::line_width`
+    Leader,
+}
+
+impl TemplateField {
+    fn from_name(name: &str) -> Option<Self> {
+        use self::TemplateField::*;
+        match name {
+            "name" => Some(Name),
+            "street" => Some(Street),
+            "locality" => Some(Locality),
+            "symbol" => Some(Symbol),
+            "category" => Some(Category),
+            "title" => Some(Title),
+            "reference" => Some(Reference),
+            "cells" => Some(Cells),
+            "page" => Some(Page),
+            "letter" => Some(Letter),
+            "leader" => Some(Leader),
+            _ => None,
+        }
+    }
+}
+
+/// Part of a template: either literal text or a field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplatePart {
+    Text(String),
+    Field(TemplateField),
+}
+
+/// Parsed template such as `"{name}{leader}{reference}"`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pub parts: Vec<TemplatePart>,
+}
+
+impl Template {
+    /// Parses a template: the fields are written in braces (see `TemplateField`),
+    /// literal braces as `{{` and `}}`
+    pub fn new(template: &str) -> Result<Self, TemplateError> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => { chars.next(); text.push('{'); },
+                '}' if chars.peek() == Some(&'}') => { chars.next(); text.push('}'); },
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(TemplateError::UnmatchedBrace),
+                        }
+                    }
+                    let field = TemplateField::from_name(name.trim()).ok_or(TemplateError::UnknownField(name))?;
+                    if !text.is_empty() {
+                        parts.push(TemplatePart::Text(::std::mem::take(&mut text)));
+                    }
+                    parts.push(TemplatePart::Field(field));
+                },
+                '}' => return Err(TemplateError::UnmatchedBrace),
+                c => text.push(c),
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(TemplatePart::Text(text));
+        }
+
+        Ok(Self { parts })
+    }
+
+    /// Empty template, writes no line
+    pub fn empty() -> Self {
+        Self { parts: Vec::new() }
+    }
+
+    /// Whether the template has no parts
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+}
+
+/// Values of the fields for one line
+#[derive(Default)]
+struct TemplateValues {
+    name: String,
+    street: String,
+    locality: String,
+    symbol: String,
+    reference: String,
+    cells: String,
+    page: String,
+    letter: String,
+}
+
+/// Writes an index with one template per entry and per section header / footer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateExport {
+    /// Template of the line of each entry
+    pub entry: Template,
+    /// Template of the line before each letter section, no line if empty
+    pub header: Template,
+    /// Template of the line after each letter section, no line if empty
+    pub footer: Template,
+    /// Character of the `{leader}` field
+    pub leader: char,
+    /// Width of a line in characters, the `{leader}` is repeated until
+    /// the line is this wide (but at least 3 times)
+    pub line_width: usize,
+    /// Pages of the grid cells for the `{page}` field, i.e. in an atlas where
+    /// each page shows a part of the grid. Cells without a page are ignored.
+    pub pages: BTreeMap<GridPosition, String>,
+    /// Letter sections of the index, see `LetterGrouping`
+    pub grouping: LetterGrouping,
+}
+
+impl TemplateExport {
+    /// Entries written with the template, no section headers or footers,
+    /// leaders of `.` up to 40 characters
+    pub fn new(entry: Template) -> Self {
+        Self {
+            entry,
+            header: Template::empty(),
+            footer: Template::empty(),
+            leader: '.',
+            line_width: 40,
+            pages: BTreeMap::new(),
+            grouping: LetterGrouping::new(),
+        }
+    }
+
+    /// Writes the entries of the index (see `ProcessedIndex::entries`), one line per
+    /// template, grouped by the initial letter. Each line ends with a newline.
+    pub fn render(&self, index: &ProcessedIndex) -> String {
+        let entries = index.entries();
+
+        // Cells of the roads, cross references have none
+        let mut cells = BTreeMap::new();
+        for road in &index.processed.processed {
+            let positions = match &road.position {
+                FinalizedGridPositon::SingleRect(a) => vec![a.clone()],
+                FinalizedGridPositon::TwoRect(a, b) => vec![a.clone(), b.clone()],
+            };
+            cells.insert((road.name.clone(), road.reference()), positions);
+        }
+        for road in &index.unprocessed.unprocessed {
+            cells.insert((road.name.clone(), road.reference()), road.positions.clone());
+        }
+
+        let mut output = String::new();
+        for section in self.grouping.sections(&entries) {
+            let section_values = TemplateValues { letter: section.letter.clone(), .. TemplateValues::default() };
+            self.push_line(&mut output, &self.header, &section_values, index);
+
+            for entry in section.entries {
+                let positions = cells.get(&(entry.name.clone(), entry.reference.clone())).cloned().unwrap_or_default();
+                self.push_line(&mut output, &self.entry, &self.entry_values(entry, &positions, &section.letter), index);
+            }
+
+            self.push_line(&mut output, &self.footer, &section_values, index);
+        }
+
+        output
+    }
+
+    fn entry_values(&self, entry: &IndexEntry, positions: &[GridPosition], letter: &str) -> TemplateValues {
+        let mut pages = Vec::new();
+        for page in positions.iter().filter_map(|position| self.pages.get(position)) {
+            if !pages.contains(&page) {
+                pages.push(page);
+            }
+        }
+
+        TemplateValues {
+            name: entry.name.to_string(),
+            street: entry.name.street_name.0.clone(),
+            locality: entry.name.locality.clone().unwrap_or_default(),
+            symbol: entry.name.symbol.clone().unwrap_or_default(),
+            reference: entry.reference.clone(),
+            cells: positions.iter().map(|position| position.to_string()).collect::<Vec<String>>().join(", "),
+            page: pages.iter().map(|page| page.as_str()).collect::<Vec<&str>>().join(", "),
+            letter: letter.to_string(),
+        }
+    }
+
+    /// Renders the template and appends it as a line, unless the template is empty
+    fn push_line(&self, output: &mut String, template: &Template, values: &TemplateValues, index: &ProcessedIndex) {
+        if template.is_empty() {
+            return;
+        }
+
+        let mut line = String::new();
+        let mut leaders = Vec::new();
+        for part in &template.parts {
+            match part {
+                TemplatePart::Text(text) => line.push_str(text),
+                TemplatePart::Field(field) => match field {
+                    TemplateField::Name => line.push_str(&values.name),
+                    TemplateField::Street => line.push_str(&values.street),
+                    TemplateField::Locality => line.push_str(&values.locality),
+                    TemplateField::Symbol => line.push_str(&values.symbol),
+                    TemplateField::Category => line.push_str(&index.category.to_string()),
+                    TemplateField::Title => line.push_str(&index.settings.title),
+                    TemplateField::Reference => line.push_str(&values.reference),
+                    TemplateField::Cells => line.push_str(&values.cells),
+                    TemplateField::Page => line.push_str(&values.page),
+                    TemplateField::Letter => line.push_str(&values.letter),
+                    TemplateField::Leader => leaders.push(line.len()),
+                },
+            }
+        }
+
+        // The space for the leaders is divided between all leader fields in the line
+        if !leaders.is_empty() {
+            let free = self.line_width.saturating_sub(line.chars().count());
+            let width = (free / leaders.len()).max(3);
+            let leader = self.leader.to_string().repeat(width);
+            for position in leaders.into_iter().rev() {
+                line.insert_str(position, &leader);
+            }
+        }
+
+        output.push_str(&line);
+        output.push('\n');
+    }
+}
+
+#[test]
+fn test_template_export() {
+    use roads2csv::{CategorizedRoads, InputStreetValue};
+
+    let input = |name: &str, column: &str| InputStreetValue::new(name, GridPosition { column: String::from(column), row: 4 });
+    let index = CategorizedRoads::from_streets(&[
+        input("Abbey Road", "B"),
+        input("Baker Street", "A"),
+        input("Baker Street", "B"),
+    ]).process().remove(0);
+
+    let export = TemplateExport::new(Template::new("{name}{leader}{reference}").unwrap());
+    assert_eq!(export.render(&index), "Abbey Road............................B4\nBaker Street.......................A4-B4\n");
+
+    let export = TemplateExport {
+        header: Template::new("{{{letter}}}").unwrap(),
+        footer: Template::new("").unwrap(),
+        pages: vec![(GridPosition { column: String::from("A"), row: 4 }, String::from("12"))].into_iter().collect(),
+        .. TemplateExport::new(Template::new("{cells} {name}, {category} p. {page}").unwrap())
+    };
+    assert_eq!(export.render(&index), "{A}\nB4 Abbey Road, Streets p. \n{B}\nA4, B4 Baker Street, Streets p. 12\n");
+
+    assert_eq!(Template::new("{name"), Err(TemplateError::UnmatchedBrace));
+    assert_eq!(Template::new("{house}"), Err(TemplateError::UnknownField(String::from("house"))));
+}
@@ -0,0 +1,90 @@
+//! Units of measurement for page coordinates
+//!
+//! Internally, everything in `street_index` is calculated in millimeter.
+//! The other units only exist so that you don't have to convert the output
+//! of your renderer by hand, i.e. `Millimeter::from(Point(72.0))` is `Millimeter(25.4)`.
+//!
+//! All values are stored as `Float`, which is `f32` by default. For large-format
+//! maps or georeferenced input, enable the `f64` feature to calculate everything
+//! in double precision.
+
+/// Floating point type used for all coordinates and lengths
+#[cfg(not(feature = "f64"))]
+pub type Float = f32;
+/// Floating point type used for all coordinates and lengths
+#[cfg(feature = "f64")]
+pub type Float = f64;
+
+/// Millimeter per inch
+const MM_PER_INCH: Float = 25.4;
+/// PostScript points per inch
+const POINTS_PER_INCH: Float = 72.0;
+
+/// Unit struct just so it's easier to read that certain values
+/// should be in millimeter scale.
+#[derive(Debug, Default, Copy, Clone, PartialEq, PartialOrd)]
+pub struct Millimeter(pub Float);
+
+/// PostScript / PDF point, 1/72 of an inch
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct Point(pub Float);
+
+/// Inch, 25.4 millimeter
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct Inch(pub Float);
+
+/// Pixel value at a certain resolution (dots per inch),
+/// i.e. for web maps that are rendered at 96 DPI
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct Pixel {
+    pub value: Float,
+    pub dpi: Float,
+}
+
+impl Pixel {
+    /// Converts a millimeter value to pixels at the given resolution
+    pub fn from_millimeter(mm: Millimeter, dpi: Float) -> Self {
+        Self { value: mm.0 / MM_PER_INCH * dpi, dpi }
+    }
+}
+
+impl From<Point> for Millimeter {
+    fn from(pt: Point) -> Self {
+        Millimeter(pt.0 / POINTS_PER_INCH * MM_PER_INCH)
+    }
+}
+
+impl From<Millimeter> for Point {
+    fn from(mm: Millimeter) -> Self {
+        Point(mm.0 / MM_PER_INCH * POINTS_PER_INCH)
+    }
+}
+
+impl From<Inch> for Millimeter {
+    fn from(inch: Inch) -> Self {
+        Millimeter(inch.0 * MM_PER_INCH)
+    }
+}
+
+impl From<Millimeter> for Inch {
+    fn from(mm: Millimeter) -> Self {
+        Inch(mm.0 / MM_PER_INCH)
+    }
+}
+
+impl From<Pixel> for Millimeter {
+    fn from(px: Pixel) -> Self {
+        Millimeter(px.value / px.dpi * MM_PER_INCH)
+    }
+}
+
+#[test]
+fn test_unit_conversion() {
+    assert_eq!(Millimeter::from(Inch(2.0)), Millimeter(50.8));
+    assert_eq!(Millimeter::from(Point(72.0)), Millimeter(25.4));
+    assert_eq!(Millimeter::from(Pixel { value: 96.0, dpi: 96.0 }), Millimeter(25.4));
+
+    assert_eq!(Point::from(Millimeter(25.4)), Point(72.0));
+    assert_eq!(Inch::from(Millimeter(50.8)), Inch(2.0));
+    assert_eq!(Pixel::from_millimeter(Millimeter(50.8), 300.0), Pixel { value: 600.0, dpi: 300.0 });
+}
@@ -0,0 +1,263 @@
+//! Export of the index as HTML and Markdown, for web publication of the map
+//!
+//! Both exports start with an a-z navigation to the letter sections
+//! (`#letter-A`) and link the grid references to the cells of the map
+//! (`#cell-B4`), so the web page of the map only has to provide these anchors.
+
+use roads2csv::IndexEntry;
+use layout::escape_xml;
+use grouping::LetterGrouping;
+
+/// Settings of the HTML export
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HtmlExport {
+    /// Heading above the index, no heading if empty
+    pub title: String,
+    /// Prefix of the anchors of the grid cells, i.e. `"cell-"` for `#cell-B4`
+    pub cell_prefix: String,
+    /// Letter sections of the index, see `LetterGrouping`
+    pub grouping: LetterGrouping,
+}
+
+impl HtmlExport {
+    /// No title, references link to `#cell-B4`
+    pub fn new() -> Self {
+        Self {
+            title: String::new(),
+            cell_prefix: String::from("cell-"),
+            grouping: LetterGrouping::new(),
+        }
+    }
+
+    /// Exports the (sorted) entries as an HTML fragment: a navigation with one link
+    /// per letter and one `<section>` per letter, with the entries as a `<dl>` list
+    pub fn html(&self, entries: &[IndexEntry]) -> String {
+        let sections = self.grouping.sections(entries);
+        let mut html = String::new();
+
+        if !self.title.is_empty() {
+            html.push_str(&format!("<h1>{}</h1>\n", escape_xml(&self.title)));
+        }
+
+        let navigation = sections.iter()
+            .map(|section| format!("<a href=\"#{}\">{}</a>", letter_anchor(&section.letter, &self.grouping), escape_xml(&section.letter)))
+            .collect::<Vec<String>>()
+            .join(" ");
+        html.push_str(&format!("<nav class=\"index-letters\">{}</nav>\n", navigation));
+
+        for section in &sections {
+            html.push_str(&format!("<section class=\"index-section\" id=\"{}\">\n<h2>{}</h2>\n<dl class=\"index-entries\">\n", letter_anchor(&section.letter, &self.grouping), escape_xml(&section.letter)));
+            for entry in &section.entries {
+                let reference = reference_parts(&entry.reference).into_iter().map(|(text, is_cell)| {
+                    if is_cell {
+                        format!("<a href=\"#{}{}\">{}</a>", escape_xml(&self.cell_prefix), text, text)
+                    } else {
+                        escape_xml(&text)
+                    }
+                }).collect::<String>();
+                html.push_str(&format!("<dt>{}</dt><dd>{}</dd>\n", escape_xml(&entry.name.to_string()), reference));
+            }
+            html.push_str("</dl>\n</section>\n");
+        }
+
+        html
+    }
+}
+
+impl Default for HtmlExport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Settings of the Markdown export
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkdownExport {
+    /// Heading above the index, no heading if empty
+    pub title: String,
+    /// Prefix of the anchors of the grid cells, i.e. `"cell-"` for `#cell-B4`.
+    /// Characters other than ASCII letters, digits and `-._~` are percent-encoded
+    /// in the links.
+    pub cell_prefix: String,
+    /// Letter sections of the index, see `LetterGrouping`
+    pub grouping: LetterGrouping,
+}
+
+impl MarkdownExport {
+    /// No title, references link to `#cell-B4`
+    pub fn new() -> Self {
+        Self {
+            title: String::new(),
+            cell_prefix: String::from("cell-"),
+            grouping: LetterGrouping::new(),
+        }
+    }
+
+    /// Exports the (sorted) entries as Markdown: a navigation line with one link
+    /// per letter and one table per letter. The anchors of the letters are HTML
+    /// anchors, since the generated heading ids differ between Markdown renderers.
+    pub fn markdown(&self, entries: &[IndexEntry]) -> String {
+        let sections = self.grouping.sections(entries);
+        let mut markdown = String::new();
+
+        if !self.title.is_empty() {
+            markdown.push_str(&format!("# {}\n\n", escape_markdown(&self.title)));
+        }
+
+        let navigation = sections.iter()
+            .map(|section| format!("[{}](#{})", escape_markdown(&section.letter), letter_anchor(&section.letter, &self.grouping)))
+            .collect::<Vec<String>>()
+            .join(" \u{b7} ");
+        markdown.push_str(&navigation);
+        markdown.push('\n');
+
+        for section in &sections {
+            markdown.push_str(&format!("\n<a id=\"{}\"></a>\n\n## {}\n\n| Name | Reference |\n| --- | --- |\n", letter_anchor(&section.letter, &self.grouping), escape_markdown(&section.letter)));
+            for entry in &section.entries {
+                let reference = reference_parts(&entry.reference).into_iter().map(|(text, is_cell)| {
+                    if is_cell {
+                        format!("[{}](#{}{})", text, percent_encode(&self.cell_prefix), text)
+                    } else {
+                        escape_markdown(&text)
+                    }
+                }).collect::<String>();
+                markdown.push_str(&format!("| {} | {} |\n", escape_markdown(&entry.name.to_string()), reference));
+            }
+        }
+
+        markdown
+    }
+}
+
+impl Default for MarkdownExport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Id of the anchor of a letter section, i.e. `"letter-A"` (`"letter-num"` for
+/// the section of the names starting with a digit). Characters that are not
+/// letters or digits are written as their hex code point: `"letter-St_2e"` for `"St."`
+fn letter_anchor(letter: &str, grouping: &LetterGrouping) -> String {
+    if letter == grouping.digits {
+        return String::from("letter-num");
+    }
+    let mut anchor = String::from("letter-");
+    for c in letter.chars() {
+        if c.is_alphanumeric() {
+            anchor.push(c);
+        } else {
+            anchor.push_str(&format!("_{:x}", c as u32));
+        }
+    }
+    anchor
+}
+
+/// Percent-encodes all characters of a link target except for ASCII letters,
+/// digits and `-._~`, so that spaces or parentheses don't end the Markdown link
+fn percent_encode(text: &str) -> String {
+    let mut encoded = String::new();
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+            byte => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Splits a reference into the grid cells (`"B4"`, `true`) and the text in between
+/// (`"1-47 "`, `false`). The targets of cross references (`"→ see Castle Road"`)
+/// are not cells, even if the name looks like one.
+fn reference_parts(reference: &str) -> Vec<(String, bool)> {
+    if reference.starts_with('\u{2192}') {
+        return vec![(reference.to_string(), false)];
+    }
+
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let chars = reference.chars().collect::<Vec<char>>();
+    let mut i = 0;
+
+    while i < chars.len() {
+        // A cell starts at a word boundary with uppercase letters, followed by digits
+        let at_word_start = i == 0 || !chars[i - 1].is_alphanumeric();
+        let letters = chars[i..].iter().take_while(|c| c.is_ascii_uppercase()).count();
+        let digits = chars[i + letters..].iter().take_while(|c| c.is_ascii_digit()).count();
+        let at_word_end = chars.get(i + letters + digits).map_or(true, |c| !c.is_alphanumeric());
+
+        if at_word_start && letters > 0 && digits > 0 && at_word_end {
+            if !text.is_empty() {
+                parts.push((::std::mem::take(&mut text), false));
+            }
+            parts.push((chars[i..i + letters + digits].iter().collect(), true));
+            i += letters + digits;
+        } else {
+            text.push(chars[i]);
+            i += 1;
+        }
+    }
+
+    if !text.is_empty() {
+        parts.push((text, false));
+    }
+
+    parts
+}
+
+/// Escapes the characters that have a special meaning in Markdown (and in tables)
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '\\' | '|' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#' => {
+                escaped.push('\\');
+                escaped.push(c);
+            },
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[test]
+fn test_web_export() {
+    let entries = [
+        IndexEntry::new("1st Avenue", "A1"),
+        IndexEntry::new("Bay <East> & West", "1-47 B2, 48-120 B3"),
+        IndexEntry::new("Bay|Road", "\u{2192} see A1 Road"),
+    ];
+
+    let html = HtmlExport::new().html(&entries);
+    assert_eq!(html.lines().collect::<Vec<_>>(), vec![
+        "<nav class=\"index-letters\"><a href=\"#letter-num\">#</a> <a href=\"#letter-B\">B</a></nav>",
+        "<section class=\"index-section\" id=\"letter-num\">",
+        "<h2>#</h2>",
+        "<dl class=\"index-entries\">",
+        "<dt>1st Avenue</dt><dd><a href=\"#cell-A1\">A1</a></dd>",
+        "</dl>",
+        "</section>",
+        "<section class=\"index-section\" id=\"letter-B\">",
+        "<h2>B</h2>",
+        "<dl class=\"index-entries\">",
+        "<dt>Bay &lt;East&gt; &amp; West</dt><dd>1-47 <a href=\"#cell-B2\">B2</a>, 48-120 <a href=\"#cell-B3\">B3</a></dd>",
+        "<dt>Bay|Road</dt><dd>\u{2192} see A1 Road</dd>",
+        "</dl>",
+        "</section>",
+    ]);
+
+    let markdown = MarkdownExport { title: String::from("Street index"), .. MarkdownExport::new() }.markdown(&entries);
+    assert!(markdown.starts_with("# Street index\n\n[\\#](#letter-num) \u{b7} [B](#letter-B)\n\n<a id=\"letter-num\"></a>\n\n## \\#\n"));
+    assert!(markdown.contains("| Bay \\<East\\> &amp; West | 1-47 [B2](#cell-B2), 48-120 [B3](#cell-B3) |\n"));
+    assert!(markdown.contains("| Bay\\|Road | \u{2192} see A1 Road |\n"));
+
+    let markdown = MarkdownExport { cell_prefix: String::from("map (p. 2) "), .. MarkdownExport::new() }.markdown(&entries[..1]);
+    assert!(markdown.contains("| 1st Avenue | [A1](#map%20%28p.%202%29%20A1) |\n"));
+
+    // Sections that aren't letters or digits get anchors of their own
+    let grouping = LetterGrouping { digits: String::from("0-9"), .. LetterGrouping::new().with_letter("St.", "S") };
+    let entries = [IndexEntry::new("1st Avenue", "A1"), IndexEntry::new("St. Mary's Road", "C2"), IndexEntry::new("Station Road", "C3")];
+    let html = HtmlExport { grouping, .. HtmlExport::new() }.html(&entries);
+    assert!(html.starts_with("<nav class=\"index-letters\"><a href=\"#letter-num\">0-9</a> <a href=\"#letter-S\">S</a> <a href=\"#letter-St_2e\">St.</a></nav>"));
+}